@@ -3,10 +3,14 @@
 //! To deal with errors from dependencies, either create a new error variant
 //! or use the MacroFailure variant if the error can only occur inside a macro.
 use crate::{
-    operator::Operator, token::PartialToken, value::value_type::ValueType, value::Value, Node,
+    operator::Operator,
+    token::{PartialToken, Token},
+    value::value_type::ValueType,
+    value::Value,
+    Node,
 };
 
-use std::{fmt, io, time::SystemTimeError};
+use std::{fmt, io, string::FromUtf8Error, time::SystemTimeError};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -19,6 +23,12 @@ pub enum Error {
         actual: usize,
     },
 
+    /// A column was inserted to a table with the wrong amount of values.
+    WrongRowAmount {
+        expected: usize,
+        actual: usize,
+    },
+
     /// An operator was called with the wrong amount of arguments.
     ExpectedOperatorArgumentAmount {
         expected: usize,
@@ -136,7 +146,12 @@ pub enum Error {
 
     /// Left of an opening brace or right of a closing brace is a token that does not expect the brace next to it.
     /// For example, writing `4(5)` would yield this error, as the `4` does not have any operands.
-    MissingOperatorOutsideOfBrace,
+    MissingOperatorOutsideOfBrace {
+        /// The token that was found next to the brace without an operator in between.
+        token: String,
+        /// The index of the offending token in the expression's token stream.
+        position: usize,
+    },
 
     /// A `PartialToken` is unmatched, such that it cannot be combined into a full `Token`.
     /// This happens if for example a single `=` is found, surrounded by whitespace.
@@ -194,6 +209,18 @@ pub enum Error {
         divisor: Value,
     },
 
+    /// An integer was divided by zero.
+    DivisionByZero {
+        /// The first argument of the division.
+        dividend: Value,
+    },
+
+    /// The remainder of an integer divided by zero was requested.
+    ModuloByZero {
+        /// The first argument of the modulation.
+        dividend: Value,
+    },
+
     /// A regular expression could not be parsed
     InvalidRegex {
         /// The invalid regular expression
@@ -217,10 +244,19 @@ pub enum Error {
     /// The function failed due to an external error.
     MacroFailure(String),
 
+    /// Evaluation was stopped by pressing Ctrl-C.
+    Interrupted,
+
     /// A custom error explained by its message.
     CustomMessage(String),
 }
 
+impl From<base64::DecodeError> for Error {
+    fn from(value: base64::DecodeError) -> Self {
+        Error::MacroFailure(value.to_string())
+    }
+}
+
 impl From<csv::Error> for Error {
     fn from(value: csv::Error) -> Self {
         Error::MacroFailure(value.to_string())
@@ -263,6 +299,12 @@ impl From<sys_info::Error> for Error {
     }
 }
 
+impl From<FromUtf8Error> for Error {
+    fn from(value: FromUtf8Error) -> Self {
+        Error::MacroFailure(value.to_string())
+    }
+}
+
 impl From<SystemTimeError> for Error {
     fn from(value: SystemTimeError) -> Self {
         Error::MacroFailure(value.to_string())
@@ -276,6 +318,17 @@ impl From<trash::Error> for Error {
 }
 
 impl Error {
+    /// Converts a count passed to a macro, failing if it is negative.
+    pub(crate) fn expect_count(identifier: &str, count: i64) -> Result<usize> {
+        if count < 0 {
+            Err(Error::CustomMessage(format!(
+                "{identifier} needs a count of zero or more, got {count}."
+            )))
+        } else {
+            Ok(count as usize)
+        }
+    }
+
     pub(crate) fn expect_operator_argument_amount(actual: usize, expected: usize) -> Result<()> {
         if actual == expected {
             Ok(())
@@ -379,11 +432,11 @@ impl Error {
         Error::ExpectedCollection { actual }
     }
 
-    pub(crate) fn unmatched_partial_token(
-        first: PartialToken,
-        second: Option<PartialToken>,
-    ) -> Self {
-        Error::UnmatchedPartialToken { first, second }
+    pub(crate) fn missing_operator_outside_of_brace(token: &Token, position: usize) -> Self {
+        Error::MissingOperatorOutsideOfBrace {
+            token: token.to_string(),
+            position,
+        }
     }
 
     pub(crate) fn addition_error(augend: Value, addend: Value) -> Self {
@@ -534,11 +587,11 @@ impl fmt::Display for Error {
             ),
             UnmatchedLBrace => write!(f, "Found an unmatched opening parenthesis '('."),
             UnmatchedRBrace => write!(f, "Found an unmatched closing parenthesis ')'."),
-            MissingOperatorOutsideOfBrace { .. } => write!(
+            MissingOperatorOutsideOfBrace { token, position } => write!(
                 f,
-                "Found an opening parenthesis that is preceded by something that does not take \
-                 any arguments on the right, or found a closing parenthesis that is succeeded by \
-                 something that does not take any arguments on the left."
+                "Found \"{token}\" at token {position} next to a parenthesis with no operator \
+                 between them. Add an operator before the opening parenthesis or after the \
+                 closing parenthesis."
             ),
             UnmatchedPartialToken { first, second } => {
                 if let Some(second) = second {
@@ -572,6 +625,14 @@ impl fmt::Display for Error {
             ModulationError { dividend, divisor } => {
                 write!(f, "Error modulating {} % {}", dividend, divisor)
             }
+            DivisionByZero { dividend } => write!(f, "Cannot divide {} by zero", dividend),
+            ModuloByZero { dividend } => {
+                write!(
+                    f,
+                    "Cannot take the remainder of {} divided by zero",
+                    dividend
+                )
+            }
             InvalidRegex { regex, message } => write!(
                 f,
                 "Regular expression {:?} is invalid: {:?}",
@@ -586,11 +647,17 @@ impl fmt::Display for Error {
             }
             IllegalEscapeSequence(string) => write!(f, "Illegal escape sequence: {}", string),
             MacroFailure(message) => write!(f, "Function failure: {}", message),
+            Interrupted => write!(f, "Interrupted"),
             CustomMessage(message) => write!(f, "Error: {}", message),
             WrongColumnAmount { expected, actual } => write!(
                 f,
                 "Wrong number of columns for this table. Expected {expected}, found {actual}."
             ),
+            WrongRowAmount { expected, actual } => write!(
+                f,
+                "Wrong number of values for this table's column. Expected one for each of the \
+                 {expected} rows, found {actual}."
+            ),
         }
     }
 }
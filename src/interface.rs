@@ -1,4 +1,4 @@
-use crate::{token, tree, Result, Value, VariableMap};
+use crate::{token, tree, Node, Operator, Result, Value, VariableMap};
 
 /// Evaluate the given expression string.
 ///
@@ -56,3 +56,171 @@ pub fn eval_with_context(string: &str, context: &mut VariableMap) -> Result<Valu
         tree::tokens_to_operator_tree(token::tokenize(string)?)?.eval_with_context_mut(context)
     }
 }
+
+/// Evaluate the given expression string with the given context and return the result
+/// of every top-level statement separated by `;`, rather than only the last one.
+///
+/// # Examples
+///
+/// ```rust
+/// # use whale_lib::*;
+/// let mut context = VariableMap::new();
+/// let results = eval_all("x = 1; x + 1; x * 10", &mut context).unwrap();
+///
+/// assert_eq!(results, vec![Value::Empty, Value::Integer(2), Value::Integer(10)]);
+/// ```
+pub fn eval_all(string: &str, context: &mut VariableMap) -> Result<Vec<Value>> {
+    let root = tree::tokens_to_operator_tree(token::tokenize(string)?)?;
+
+    match root.children() {
+        [chain] if chain.operator() == &Operator::Chain => chain
+            .children()
+            .iter()
+            .map(|statement| statement.eval_with_context_mut(context))
+            .collect(),
+        _ => Ok(vec![root.eval_with_context_mut(context)?]),
+    }
+}
+
+/// Split the given source into tokens without evaluating it. Each token is returned as
+/// its display string along with the byte offset where it starts in the source.
+///
+/// # Examples
+///
+/// ```rust
+/// # use whale_lib::*;
+/// let tokens = lex("a + 1").unwrap();
+///
+/// assert_eq!(tokens[1], ("+".to_string(), 2));
+/// ```
+pub fn lex(source: &str) -> Result<Vec<(String, usize)>> {
+    let tokens = token::tokenize_with_offsets(source)?
+        .into_iter()
+        .map(|(token, offset)| (token.to_string(), offset))
+        .collect();
+
+    Ok(tokens)
+}
+
+/// Build the operator tree for the given source without evaluating it. Use
+/// [Node::children] and [Node::operator] to walk the tree.
+///
+/// # Examples
+///
+/// ```rust
+/// # use whale_lib::*;
+/// let root = parse("1 + 2").unwrap();
+///
+/// assert_eq!(root.children()[0].operator(), &Operator::Add);
+/// ```
+pub fn parse(source: &str) -> Result<Node> {
+    tree::tokens_to_operator_tree(token::tokenize(source)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eval_all_statements() {
+        let mut context = VariableMap::new();
+        let results = eval_all("x = 1; x + 1; x * 10", &mut context);
+
+        assert_eq!(
+            Ok(vec![Value::Empty, Value::Integer(2), Value::Integer(10)]),
+            results
+        );
+        assert_eq!(Some(Value::Integer(1)), context.get_value("x").unwrap());
+    }
+
+    #[test]
+    fn eval_all_single_statement() {
+        let mut context = VariableMap::new();
+
+        assert_eq!(Ok(vec![Value::Integer(3)]), eval_all("1 + 2", &mut context));
+    }
+
+    #[test]
+    fn lex_spans() {
+        assert_eq!(
+            Ok(vec![
+                ("a".to_string(), 0),
+                ("+".to_string(), 2),
+                ("1".to_string(), 4)
+            ]),
+            lex("a + 1")
+        );
+    }
+
+    #[test]
+    fn lex_offsets_after_multibyte_strings() {
+        let tokens = lex("x = \"🐋\" += 1.5e-3").unwrap();
+        let offsets: Vec<usize> = tokens.iter().map(|(_, offset)| *offset).collect();
+
+        assert_eq!(vec![0, 2, 4, 11, 14], offsets);
+    }
+
+    #[test]
+    fn parse_respects_precedence() {
+        let root = parse("1 + 2 * 3").unwrap();
+
+        assert_eq!(&Operator::RootNode, root.operator());
+        assert_eq!(1, root.children().len());
+
+        let addition = &root.children()[0];
+
+        assert_eq!(&Operator::Add, addition.operator());
+        assert_eq!(
+            &Operator::value(Value::Integer(1)),
+            addition.children()[0].operator()
+        );
+
+        let multiplication = &addition.children()[1];
+
+        assert_eq!(&Operator::Mul, multiplication.operator());
+        assert_eq!(
+            vec![
+                &Operator::value(Value::Integer(2)),
+                &Operator::value(Value::Integer(3))
+            ],
+            multiplication
+                .children()
+                .iter()
+                .map(Node::operator)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn assign_to_list_item() {
+        let mut context = VariableMap::new();
+
+        eval_with_context("x = (1, 2, 3); x.0 = 5; x.2 += 1", &mut context).unwrap();
+
+        assert_eq!(
+            Some(Value::List(vec![
+                Value::Integer(5),
+                Value::Integer(2),
+                Value::Integer(4)
+            ])),
+            context.get_value("x").unwrap()
+        );
+        assert!(eval_with_context("x.3 = 1", &mut context).is_err());
+    }
+
+    #[test]
+    fn assign_to_nested_map_field() {
+        let mut context = VariableMap::new();
+
+        eval_with_context(
+            "config.server.port = 80; config.server.port = 8080",
+            &mut context,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(Value::Integer(8080)),
+            context.get_value("config.server.port").unwrap()
+        );
+    }
+}
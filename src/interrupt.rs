@@ -0,0 +1,122 @@
+//! Ctrl-C handling for interactive use.
+//!
+//! Once [set_interrupt_handler] has been called, pressing Ctrl-C sets a flag instead of
+//! killing the process. The evaluator checks that flag before every node and stops with
+//! [Error::Interrupted][crate::Error::Interrupted] when it is set. Macros that block for a
+//! long time, such as `download` or `wait`, check it while they work. Pressing Ctrl-C a
+//! second time before the flag is cleared exits the process.
+use std::{
+    process::{exit, Child, ExitStatus},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Once,
+    },
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, Result};
+
+pub(crate) static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+static SET_INTERRUPT_HANDLER: Once = Once::new();
+
+/// Catch Ctrl-C so that it interrupts the current evaluation instead of exiting.
+///
+/// Calling this more than once has no further effect.
+pub fn set_interrupt_handler() {
+    SET_INTERRUPT_HANDLER.call_once(|| {
+        let _ = ctrlc::set_handler(|| {
+            if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                exit(130);
+            }
+        });
+    });
+}
+
+/// Returns true if Ctrl-C was pressed since the last call to [clear_interrupt].
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Reset the interrupt flag, usually before starting a new evaluation.
+pub fn clear_interrupt() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+/// How often blocking operations check the interrupt flag.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Returns [Error::Interrupted] if the given flag is set.
+pub(crate) fn check_interrupt(interrupted: &AtomicBool) -> Result<()> {
+    if interrupted.load(Ordering::SeqCst) {
+        Err(Error::Interrupted)
+    } else {
+        Ok(())
+    }
+}
+
+/// Sleep for the given duration, waking up early with [Error::Interrupted] if the flag is
+/// set.
+pub(crate) fn sleep_interruptible(duration: Duration, interrupted: &AtomicBool) -> Result<()> {
+    let started = Instant::now();
+
+    while let Some(remaining) = duration.checked_sub(started.elapsed()) {
+        check_interrupt(interrupted)?;
+        sleep(remaining.min(POLL_INTERVAL));
+    }
+
+    Ok(())
+}
+
+/// Wait for a child process to exit. If the flag is set first, the child is killed and
+/// [Error::Interrupted] is returned.
+pub(crate) fn wait_interruptible(
+    child: &mut Child,
+    interrupted: &AtomicBool,
+) -> Result<ExitStatus> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        if interrupted.load(Ordering::SeqCst) {
+            let _ = child.kill();
+            let _ = child.wait();
+
+            return Err(Error::Interrupted);
+        }
+
+        sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_is_interrupted() {
+        let interrupted = AtomicBool::new(true);
+        let started = Instant::now();
+        let result = sleep_interruptible(Duration::from_secs(10), &interrupted);
+
+        assert_eq!(result, Err(Error::Interrupted));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn child_is_killed_when_interrupted() {
+        let interrupted = AtomicBool::new(true);
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg("sleep 10")
+            .spawn()
+            .unwrap();
+        let started = Instant::now();
+        let result = wait_interruptible(&mut child, &interrupted);
+
+        assert_eq!(result, Err(Error::Interrupted));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -4,18 +4,24 @@
 pub use crate::{
     error::{Error, Result},
     interface::*,
+    interrupt::{clear_interrupt, is_interrupted, set_interrupt_handler},
     macros::*,
     operator::Operator,
     token::PartialToken,
     tree::Node,
     value::{
-        function::Function, table::Table, time::Time, value_type::ValueType,
-        variable_map::VariableMap, Value,
+        function::Function,
+        table::{ColumnAlignment, Table, TableStyle},
+        time::Time,
+        value_type::ValueType,
+        variable_map::VariableMap,
+        Value,
     },
 };
 
 mod error;
 mod interface;
+mod interrupt;
 mod macros;
 mod operator;
 mod token;
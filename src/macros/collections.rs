@@ -1,6 +1,14 @@
 //! Macros for collection values: strings, lists, maps and tables.
 
-use crate::{Error, Macro, MacroInfo, Result, Table, Value, ValueType, VariableMap};
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+use super::data_formats::parse_decimal;
+use crate::{
+    ColumnAlignment, Error, Macro, MacroInfo, Result, Table, TableStyle, Value, ValueType,
+    VariableMap,
+};
 
 pub struct String;
 
@@ -31,6 +39,116 @@ impl Macro for String {
     }
 }
 
+pub struct ApplyToColumn;
+
+impl Macro for ApplyToColumn {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "apply_to_column",
+            description: "Replace each cell in a table column with the result of a function. The cell is bound to `input`.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(3)?;
+        let mut table = argument[0].as_table()?.clone();
+        let column_name = argument[1].as_string()?;
+        let function = argument[2].as_function()?;
+        let mut context = VariableMap::new();
+
+        table.map_column(column_name, |cell| {
+            context.set_value("input", cell.clone())?;
+            function.run_with_context(&mut context)
+        })?;
+
+        Ok(Value::Table(table))
+    }
+}
+
+pub struct CoalesceColumns;
+
+impl Macro for CoalesceColumns {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "coalesce_columns",
+            description: "Add a column with the first non-empty value from a list of columns. Takes [table, new_name, columns].",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(3)?;
+        let mut table = argument[0].as_table()?.clone();
+        let name = argument[1].as_string()?.clone();
+        let sources = argument[2]
+            .as_list()?
+            .iter()
+            .map(|column| column.as_string().cloned())
+            .collect::<Result<Vec<_>>>()?;
+
+        table.coalesce_columns(name, &sources)?;
+
+        Ok(Value::Table(table))
+    }
+}
+
+pub struct CoerceTypes;
+
+impl Macro for CoerceTypes {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "coerce_types",
+            description: "Convert each table column to integers, floats or booleans if every cell in it can be read that way.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let table = argument.as_table()?;
+        let mut rows = table.rows().clone();
+
+        for column_index in 0..table.column_names().len() {
+            let cells = || rows.iter().filter_map(|row| row.get(column_index));
+            let target = [ValueType::Int, ValueType::Float, ValueType::Boolean]
+                .iter()
+                .find(|value_type| cells().all(|cell| coerce(cell, value_type).is_some()));
+
+            if let Some(value_type) = target {
+                for row in rows.iter_mut() {
+                    if let Some(cell) = row.get_mut(column_index) {
+                        *cell = coerce(cell, value_type).unwrap();
+                    }
+                }
+            }
+        }
+
+        let mut coerced = Table::new(table.column_names().clone());
+
+        for row in rows {
+            coerced.insert(row)?;
+        }
+
+        Ok(Value::Table(coerced))
+    }
+}
+
+/// Reads a cell as the given type, parsing strings if needed.
+fn coerce(cell: &Value, value_type: &ValueType) -> Option<Value> {
+    match (cell, value_type) {
+        (Value::Integer(_), ValueType::Int)
+        | (Value::Float(_), ValueType::Float)
+        | (Value::Boolean(_), ValueType::Boolean) => Some(cell.clone()),
+        (Value::Integer(integer), ValueType::Float) => Some(Value::Float(*integer as f64)),
+        (Value::String(string), ValueType::Int) => string.trim().parse().ok().map(Value::Integer),
+        (Value::String(string), ValueType::Float) => parse_decimal(string.trim()).map(Value::Float),
+        (Value::String(string), ValueType::Boolean) => {
+            string.trim().parse().ok().map(Value::Boolean)
+        }
+        _ => None,
+    }
+}
+
 pub struct Count;
 
 impl Macro for Count {
@@ -60,6 +178,32 @@ impl Macro for Count {
     }
 }
 
+pub struct AddColumn;
+
+impl Macro for AddColumn {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "add_column",
+            description: "Add a named column to a table with one value for each row.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_list()?;
+
+        Error::expect_function_argument_amount(self.info().identifier, argument.len(), 3)?;
+
+        let mut table = argument[0].as_table()?.clone();
+        let name = argument[1].as_string()?.clone();
+        let values = argument[2].as_list()?.clone();
+
+        table.insert_column(name, values)?;
+
+        Ok(Value::Table(table))
+    }
+}
+
 pub struct CreateTable;
 
 impl Macro for CreateTable {
@@ -95,6 +239,212 @@ impl Macro for CreateTable {
     }
 }
 
+pub struct Tabulate;
+
+impl Macro for Tabulate {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "tabulate",
+            description: "Build a table from a list of rows and a list of column names.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let rows = argument[0].as_list()?;
+        let column_names = argument[1]
+            .as_list()?
+            .iter()
+            .map(|name| name.as_string().cloned())
+            .collect::<Result<Vec<_>>>()?;
+        let mut table = Table::new(column_names);
+
+        for row in rows {
+            table.insert(row.as_list()?.clone())?;
+        }
+
+        Ok(Value::Table(table))
+    }
+}
+
+pub struct Merge;
+
+impl Macro for Merge {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "merge",
+            description:
+                "Combine two maps. Values from the second map win, nested maps are merged.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let mut merged = argument[0].as_map()?.clone();
+
+        merged.extend(argument[1].as_map()?);
+
+        Ok(Value::Map(merged))
+    }
+}
+
+pub struct RenderTable;
+
+impl Macro for RenderTable {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "render_table",
+            description: "Draw a table as text. Pass a border style (ascii, rounded, markdown or none) or a map with \"style\" and an \"align\" map of column names to left, center or right.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let table = argument[0].as_table()?;
+
+        if let Ok(style) = argument[1].as_string() {
+            return Ok(Value::String(table.render(TableStyle::from_name(style)?)));
+        }
+
+        let options = argument[1].as_map()?;
+        let style = match options.get_value("style")? {
+            Some(style) => TableStyle::from_name(style.as_string()?)?,
+            None => TableStyle::default(),
+        };
+        let mut alignments = BTreeMap::new();
+
+        if let Some(align) = options.get_value("align")? {
+            for (column_name, alignment) in align.as_map()?.inner() {
+                table.column_index_or_err(column_name)?;
+
+                alignments.insert(
+                    column_name.clone(),
+                    ColumnAlignment::from_name(alignment.as_string()?)?,
+                );
+            }
+        }
+
+        Ok(Value::String(table.render_aligned(style, &alignments)))
+    }
+}
+
+pub struct Pop;
+
+impl Macro for Pop {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "pop",
+            description: "Remove the last item from a list. Returns [rest, last], or empty if the list is empty.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let mut list = argument.as_list()?.clone();
+
+        match list.pop() {
+            Some(last) => Ok(Value::List(vec![Value::List(list), last])),
+            None => Ok(Value::Empty),
+        }
+    }
+}
+
+pub struct Push;
+
+impl Macro for Push {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "push",
+            description: "Return a copy of a list with a value added to the end.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let mut list = argument[0].as_list()?.clone();
+
+        list.push(argument[1].clone());
+
+        Ok(Value::List(list))
+    }
+}
+
+pub struct Rank;
+
+impl Macro for Rank {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "rank",
+            description: "Return the 1-based rank of each item in a list, in \"asc\" or \"desc\" order. Equal items share a rank. Pass \"dense\" as a third argument to rank without gaps after ties.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_list()?;
+
+        Error::expected_minimum_function_argument_amount(
+            self.info().identifier,
+            argument.len(),
+            2,
+        )?;
+
+        let list = argument[0].as_list()?;
+        let descending = match argument[1].as_string()?.as_str() {
+            "asc" => false,
+            "desc" => true,
+            order => {
+                return Err(Error::MacroFailure(format!(
+                    "The order must be \"asc\" or \"desc\", not \"{order}\"."
+                )))
+            }
+        };
+        let dense = match argument.get(2) {
+            Some(mode) => match mode.as_string()?.as_str() {
+                "dense" => true,
+                "standard" => false,
+                mode => {
+                    return Err(Error::MacroFailure(format!(
+                        "The tie mode must be \"dense\" or \"standard\", not \"{mode}\"."
+                    )))
+                }
+            },
+            None => false,
+        };
+        let mut order: Vec<usize> = (0..list.len()).collect();
+
+        order.sort_by(|left, right| {
+            let ordering = compare_values(&list[*left], &list[*right]);
+
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        let mut ranks = vec![Value::Empty; list.len()];
+        let mut rank = 0;
+
+        for (position, index) in order.iter().enumerate() {
+            let is_tied = position > 0
+                && compare_values(&list[order[position - 1]], &list[*index]) == Ordering::Equal;
+
+            if !is_tied {
+                rank = if dense { rank + 1 } else { position as i64 + 1 };
+            }
+
+            ranks[*index] = Value::Integer(rank);
+        }
+
+        Ok(Value::List(ranks))
+    }
+}
+
 pub struct Rows;
 
 impl Macro for Rows {
@@ -119,6 +469,360 @@ impl Macro for Rows {
     }
 }
 
+pub struct Head;
+
+impl Macro for Head {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "head",
+            description:
+                "Return the first n rows of a table or items of a list. The default is 10.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let (collection, count) = collection_and_count(self.info().identifier, argument)?;
+
+        match collection {
+            Value::List(list) => {
+                let end = count.min(list.len());
+
+                Ok(Value::List(list[..end].to_vec()))
+            }
+            Value::Table(table) => {
+                let end = count.min(table.len());
+                let mut head = Table::new(table.column_names().clone());
+
+                head.reserve(end);
+
+                for row in &table.rows()[..end] {
+                    head.insert(row.clone())?;
+                }
+
+                Ok(Value::Table(head))
+            }
+            _ => Err(Error::TypeError {
+                expected: &[ValueType::List, ValueType::Table],
+                actual: collection.clone(),
+            }),
+        }
+    }
+}
+
+pub struct Tail;
+
+impl Macro for Tail {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "tail",
+            description: "Return the last n rows of a table or items of a list. The default is 10.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let (collection, count) = collection_and_count(self.info().identifier, argument)?;
+
+        match collection {
+            Value::List(list) => {
+                let start = list.len().saturating_sub(count);
+
+                Ok(Value::List(list[start..].to_vec()))
+            }
+            Value::Table(table) => {
+                let start = table.len().saturating_sub(count);
+                let mut tail = Table::new(table.column_names().clone());
+
+                tail.reserve(table.len() - start);
+
+                for row in &table.rows()[start..] {
+                    tail.insert(row.clone())?;
+                }
+
+                Ok(Value::Table(tail))
+            }
+            _ => Err(Error::TypeError {
+                expected: &[ValueType::List, ValueType::Table],
+                actual: collection.clone(),
+            }),
+        }
+    }
+}
+
+/// Split the argument of `head` and `tail` into the collection and the number
+/// of items to keep. A two-item list ending in an integer is always read as
+/// [collection, count]; anything else is the collection with the default count.
+fn collection_and_count<'a>(identifier: &str, argument: &'a Value) -> Result<(&'a Value, usize)> {
+    const DEFAULT_COUNT: usize = 10;
+
+    if let Value::List(list) = argument {
+        if let [collection, Value::Integer(count)] = list.as_slice() {
+            return Ok((collection, Error::expect_count(identifier, *count)?));
+        }
+    }
+
+    Ok((argument, DEFAULT_COUNT))
+}
+
+pub struct TopN;
+
+impl Macro for TopN {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "top_n",
+            description: "Return the n rows or items with the largest keys. The key is a column name for tables and an optional function for lists.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        select_by_key(self.info().identifier, argument, Ordering::Greater)
+    }
+}
+
+pub struct BottomN;
+
+impl Macro for BottomN {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "bottom_n",
+            description: "Return the n rows or items with the smallest keys. The key is a column name for tables and an optional function for lists.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        select_by_key(self.info().identifier, argument, Ordering::Less)
+    }
+}
+
+/// Shared implementation of `top_n` and `bottom_n`. Items whose keys compare as `first` come
+/// first in the result. Ties keep their original order.
+fn select_by_key(identifier: &str, argument: &Value, first: Ordering) -> Result<Value> {
+    let argument = argument.as_list()?;
+
+    Error::expected_minimum_function_argument_amount(identifier, argument.len(), 2)?;
+
+    let collection = &argument[0];
+    let count = Error::expect_count(identifier, argument[1].as_int()?)?;
+    let key = argument.get(2);
+
+    match collection {
+        Value::Table(table) => {
+            let column_name = key
+                .ok_or_else(|| Error::MacroFailure(format!("{identifier} needs a column name")))?
+                .as_string()?;
+            let column_index = table.column_index_or_err(column_name)?;
+            let keys = table
+                .rows()
+                .iter()
+                .map(|row| row[column_index].clone())
+                .collect();
+            let mut selected = Table::new(table.column_names().clone());
+
+            for index in partial_select(keys, count, first) {
+                selected.insert(table.rows()[index].clone())?;
+            }
+
+            Ok(Value::Table(selected))
+        }
+        Value::List(list) => {
+            let keys = match key {
+                Some(function) => {
+                    let function = function.as_function()?;
+                    let mut context = VariableMap::new();
+                    let mut keys = Vec::with_capacity(list.len());
+
+                    for value in list {
+                        context.set_value("input", value.clone())?;
+                        keys.push(function.run_with_context(&mut context)?);
+                    }
+
+                    keys
+                }
+                None => list.clone(),
+            };
+            let selected = partial_select(keys, count, first)
+                .into_iter()
+                .map(|index| list[index].clone())
+                .collect();
+
+            Ok(Value::List(selected))
+        }
+        _ => Err(Error::TypeError {
+            expected: &[ValueType::List, ValueType::Table],
+            actual: collection.clone(),
+        }),
+    }
+}
+
+/// Orders two values like [Value::cmp], except that integers and floats are compared by
+/// their numeric value instead of by type. This is a total order, so it is safe to sort with.
+fn compare_values(left: &Value, right: &Value) -> Ordering {
+    match (left, right) {
+        (Value::Integer(left), Value::Integer(right)) => left.cmp(right),
+        (Value::Float(left), Value::Float(right)) => left.total_cmp(right),
+        (Value::Integer(integer), Value::Float(float)) => {
+            compare_integer_to_float(*integer, *float)
+        }
+        (Value::Float(float), Value::Integer(integer)) => {
+            compare_integer_to_float(*integer, *float).reverse()
+        }
+        // Floats rank with the integers against other types so that numbers stay together.
+        (Value::Float(_), _) => Value::Integer(0).cmp(right),
+        (_, Value::Float(_)) => left.cmp(&Value::Integer(0)),
+        _ => left.cmp(right),
+    }
+}
+
+/// Compares an integer to a float exactly, without rounding the integer to a float. NaNs and
+/// negative zero are placed where [f64::total_cmp] puts them among the other floats.
+fn compare_integer_to_float(integer: i64, float: f64) -> Ordering {
+    // 2^63, the first float above every i64.
+    const I64_END: f64 = 9_223_372_036_854_775_808.0;
+
+    if float.is_nan() {
+        return (integer as f64).total_cmp(&float);
+    }
+
+    if float >= I64_END {
+        return Ordering::Less;
+    }
+
+    if float < -I64_END {
+        return Ordering::Greater;
+    }
+
+    let whole = float.trunc();
+
+    match integer.cmp(&(whole as i64)) {
+        Ordering::Equal if float > whole => Ordering::Less,
+        Ordering::Equal if float < whole => Ordering::Greater,
+        Ordering::Equal if float.is_sign_negative() && float == 0.0 => Ordering::Greater,
+        ordering => ordering,
+    }
+}
+
+/// Returns the indexes of the `count` keys that sort first, in order. Only those keys are
+/// sorted; the rest are partitioned away.
+fn partial_select(keys: Vec<Value>, count: usize, first: Ordering) -> Vec<usize> {
+    let mut indexed: Vec<(usize, Value)> = keys.into_iter().enumerate().collect();
+    let compare = |(left_index, left): &(usize, Value), (right_index, right): &(usize, Value)| {
+        if compare_values(left, right) == first {
+            Ordering::Less
+        } else if compare_values(right, left) == first {
+            Ordering::Greater
+        } else {
+            left_index.cmp(right_index)
+        }
+    };
+
+    if count < indexed.len() {
+        if count > 0 {
+            indexed.select_nth_unstable_by(count - 1, compare);
+        }
+
+        indexed.truncate(count);
+    }
+
+    indexed.sort_by(compare);
+    indexed.into_iter().map(|(index, _)| index).collect()
+}
+
+pub struct Crosstab;
+
+impl Macro for Crosstab {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "crosstab",
+            description: "Count how often the values of two table columns occur together. Takes [table, row_column, column_column].",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(3)?;
+        let table = argument[0].as_table()?;
+        let row_column = argument[1].as_string()?;
+        let column_column = argument[2].as_string()?;
+
+        Ok(Value::Table(table.crosstab(row_column, column_column)?))
+    }
+}
+
+pub struct FillEmpty;
+
+impl Macro for FillEmpty {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "fill_empty",
+            description: "Replace every empty cell in a table with a value.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let mut table = argument[0].as_table()?.clone();
+
+        table.fill_empty(&argument[1]);
+
+        Ok(Value::Table(table))
+    }
+}
+
+pub struct FillForward;
+
+impl Macro for FillForward {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "fill_forward",
+            description: "Replace every empty cell in a table with the last value above it.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let mut table = argument.as_table()?.clone();
+
+        table.fill_forward();
+
+        Ok(Value::Table(table))
+    }
+}
+
+pub struct Explode;
+
+impl Macro for Explode {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "explode",
+            description: "Repeat each table row once for every item of the list in a column. Pass true as a third argument to keep rows with empty lists.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_list()?;
+
+        Error::expected_minimum_function_argument_amount(
+            self.info().identifier,
+            argument.len(),
+            2,
+        )?;
+
+        let table = argument[0].as_table()?;
+        let column_name = argument[1].as_string()?;
+        let keep_empty = match argument.get(2) {
+            Some(keep_empty) => keep_empty.as_boolean()?,
+            None => false,
+        };
+
+        Ok(Value::Table(table.explode(column_name, keep_empty)?))
+    }
+}
+
 pub struct Get;
 
 impl Macro for Get {
@@ -175,8 +879,88 @@ impl Macro for Insert {
             table.insert(row)?;
         }
 
-        Ok(Value::Table(table))
+        Ok(Value::Table(table))
+    }
+}
+
+/// The most items `sample` will pick with replacement, where the count is not bounded by
+/// the size of the input.
+const MAX_SAMPLE_COUNT: usize = 1_000_000;
+
+pub struct Sample;
+
+impl Macro for Sample {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "sample",
+            description: "Pick random items from a list or rows from a table. Pass true as a third argument to allow picking the same one twice.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_list()?;
+
+        Error::expected_minimum_function_argument_amount(
+            self.info().identifier,
+            argument.len(),
+            2,
+        )?;
+
+        let count = Error::expect_count(self.info().identifier, argument[1].as_int()?)?;
+        let with_replacement = match argument.get(2) {
+            Some(with_replacement) => with_replacement.as_boolean()?,
+            None => false,
+        };
+
+        if with_replacement && count > MAX_SAMPLE_COUNT {
+            return Err(Error::CustomMessage(format!(
+                "Cannot sample more than {MAX_SAMPLE_COUNT} items with replacement, got {count}."
+            )));
+        }
+
+        let mut rng = thread_rng();
+
+        match &argument[0] {
+            Value::Table(table) => {
+                let rows = sample(table.rows(), count, with_replacement, &mut rng);
+                let mut sampled = Table::new(table.column_names().clone());
+
+                for row in rows {
+                    sampled.insert(row)?;
+                }
+
+                Ok(Value::Table(sampled))
+            }
+            collection => {
+                let list = collection.as_list()?;
+
+                Ok(Value::List(sample(list, count, with_replacement, &mut rng)))
+            }
+        }
+    }
+}
+
+/// Picks `count` random items. Without replacement, asking for more items than there are
+/// returns all of them in random order.
+fn sample<T: Clone>(
+    items: &[T],
+    count: usize,
+    with_replacement: bool,
+    rng: &mut impl Rng,
+) -> Vec<T> {
+    if with_replacement {
+        return (0..count)
+            .filter_map(|_| items.choose(rng).cloned())
+            .collect();
     }
+
+    let mut sampled = items.to_vec();
+
+    sampled.shuffle(rng);
+    sampled.truncate(count);
+
+    sampled
 }
 
 pub struct Select;
@@ -286,6 +1070,33 @@ impl Macro for ForEach {
     }
 }
 
+pub struct ToMap;
+
+impl Macro for ToMap {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "to_map",
+            description:
+                "Create a map from a list of [key, value] pairs. Later keys replace earlier ones.",
+            group: "collections",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let pairs = argument.as_list()?;
+        let mut map = BTreeMap::new();
+
+        for pair in pairs {
+            let pair = pair.as_fixed_len_list(2)?;
+            let key = pair[0].as_string()?;
+
+            map.insert(key.to_string(), pair[1].clone());
+        }
+
+        Ok(Value::Map(VariableMap::from(map)))
+    }
+}
+
 pub struct Where;
 
 impl Macro for Where {
@@ -376,6 +1187,155 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn add_column() {
+        let mut table = Table::new(vec!["foo".to_string()]);
+
+        table.insert(vec![Value::Integer(1)]).unwrap();
+        table.insert(vec![Value::Integer(2)]).unwrap();
+
+        let arguments = Value::List(vec![
+            Value::Table(table),
+            Value::String("bar".to_string()),
+            Value::List(vec![Value::Integer(3), Value::Integer(4)]),
+        ]);
+        let result = AddColumn.run(&arguments).unwrap();
+        let mut table = Table::new(vec!["foo".to_string(), "bar".to_string()]);
+
+        table
+            .insert(vec![Value::Integer(1), Value::Integer(3)])
+            .unwrap();
+        table
+            .insert(vec![Value::Integer(2), Value::Integer(4)])
+            .unwrap();
+
+        assert_eq!(Value::Table(table), result);
+    }
+
+    fn create_numbered_table(row_count: i64) -> Table {
+        let mut table = Table::new(vec!["id".to_string()]);
+
+        for id in 0..row_count {
+            table.insert(vec![Value::Integer(id)]).unwrap();
+        }
+
+        table
+    }
+
+    #[test]
+    fn to_map_from_pairs() {
+        let pair = |key: &str, value: i64| {
+            Value::List(vec![Value::String(key.to_string()), Value::Integer(value)])
+        };
+        let mut expected = VariableMap::new();
+
+        expected.set_value("a", Value::Integer(3)).unwrap();
+        expected.set_value("b", Value::Integer(2)).unwrap();
+
+        assert_eq!(
+            Ok(Value::Map(expected)),
+            ToMap.run(&Value::List(vec![pair("a", 1), pair("b", 2), pair("a", 3)]))
+        );
+        assert_eq!(
+            Err(Error::ExpectedString {
+                actual: Value::Integer(1)
+            }),
+            ToMap.run(&Value::List(vec![Value::List(vec![
+                Value::Integer(1),
+                Value::Integer(2)
+            ])]))
+        );
+    }
+
+    #[test]
+    fn to_map_keeps_dotted_keys() {
+        let pair = Value::List(vec![Value::String("a.b".to_string()), Value::Integer(1)]);
+        let map = ToMap.run(&Value::List(vec![pair])).unwrap();
+        let map = map.as_map().unwrap();
+
+        assert_eq!(vec!["a.b"], map.keys().collect::<Vec<_>>());
+        assert_eq!(Some(&Value::Integer(1)), map.inner().get("a.b"));
+    }
+
+    #[test]
+    fn head_from_table() {
+        let table = Value::Table(create_numbered_table(20));
+
+        assert_eq!(
+            Value::Table(create_numbered_table(10)),
+            Head.run(&table).unwrap()
+        );
+        assert_eq!(
+            Value::Table(create_numbered_table(3)),
+            Head.run(&Value::List(vec![table.clone(), Value::Integer(3)]))
+                .unwrap()
+        );
+        assert_eq!(
+            table.clone(),
+            Head.run(&Value::List(vec![table, Value::Integer(50)]))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn head_and_tail_from_list() {
+        let list = Value::List((0..20).map(Value::Integer).collect());
+
+        assert_eq!(
+            Value::List((0..10).map(Value::Integer).collect()),
+            Head.run(&list).unwrap()
+        );
+        assert_eq!(
+            Value::List((10..20).map(Value::Integer).collect()),
+            Tail.run(&list).unwrap()
+        );
+        assert_eq!(
+            Value::List(vec![Value::Integer(18), Value::Integer(19)]),
+            Tail.run(&Value::List(vec![list, Value::Integer(2)]))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn head_of_nested_list_with_count() {
+        let list = Value::List(vec![
+            Value::Integer(1),
+            Value::Integer(2),
+            Value::Integer(3),
+        ]);
+
+        assert_eq!(
+            Value::List(vec![Value::Integer(1), Value::Integer(2)]),
+            Head.run(&Value::List(vec![list.clone(), Value::Integer(2)]))
+                .unwrap()
+        );
+        assert_eq!(
+            Value::List(vec![Value::Integer(3)]),
+            Tail.run(&Value::List(vec![list, Value::Integer(1)]))
+                .unwrap()
+        );
+        assert!(Head
+            .run(&Value::List(vec![Value::Integer(7), Value::Integer(8)]))
+            .is_err());
+    }
+
+    #[test]
+    fn tail_from_table() {
+        let table = Value::Table(create_numbered_table(20));
+        let mut expected = Table::new(vec!["id".to_string()]);
+
+        for id in 17..20 {
+            expected.insert(vec![Value::Integer(id)]).unwrap();
+        }
+
+        assert_eq!(
+            Value::Table(expected),
+            Tail.run(&Value::List(vec![table.clone(), Value::Integer(3)]))
+                .unwrap()
+        );
+        assert_eq!(10, Tail.run(&table).unwrap().as_table().unwrap().len());
+    }
+
     #[test]
     fn where_from_non_collections() {
         Where
@@ -515,4 +1475,540 @@ mod tests {
 
         assert_eq!(Value::Table(table), select);
     }
+
+    #[test]
+    fn tabulate() {
+        let rows = Value::List(vec![
+            Value::List(vec![Value::String("a".to_string()), Value::Integer(1)]),
+            Value::List(vec![Value::String("b".to_string()), Value::Integer(2)]),
+        ]);
+        let column_names = Value::List(vec![
+            Value::String("name".to_string()),
+            Value::String("count".to_string()),
+        ]);
+        let result = Tabulate
+            .run(&Value::List(vec![rows, column_names.clone()]))
+            .unwrap();
+        let mut table = Table::new(vec!["name".to_string(), "count".to_string()]);
+
+        table
+            .insert(vec![Value::String("a".to_string()), Value::Integer(1)])
+            .unwrap();
+        table
+            .insert(vec![Value::String("b".to_string()), Value::Integer(2)])
+            .unwrap();
+
+        assert_eq!(Value::Table(table), result);
+
+        let uneven_rows = Value::List(vec![Value::List(vec![Value::Integer(1)])]);
+
+        assert_eq!(
+            Err(Error::WrongColumnAmount {
+                expected: 2,
+                actual: 1
+            }),
+            Tabulate.run(&Value::List(vec![uneven_rows, column_names]))
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut left = VariableMap::new();
+        let mut right = VariableMap::new();
+
+        left.set_value("a", Value::Integer(1)).unwrap();
+        left.set_value("b", Value::Integer(2)).unwrap();
+        left.set_value("nested.x", Value::Integer(1)).unwrap();
+        right.set_value("b", Value::Integer(3)).unwrap();
+        right.set_value("nested.y", Value::Integer(2)).unwrap();
+
+        let result = Merge
+            .run(&Value::List(vec![Value::Map(left), Value::Map(right)]))
+            .unwrap();
+        let mut expected = VariableMap::new();
+
+        expected.set_value("a", Value::Integer(1)).unwrap();
+        expected.set_value("b", Value::Integer(3)).unwrap();
+        expected.set_value("nested.x", Value::Integer(1)).unwrap();
+        expected.set_value("nested.y", Value::Integer(2)).unwrap();
+
+        assert_eq!(Value::Map(expected), result);
+    }
+
+    #[test]
+    fn render_table_with_options() {
+        let mut table = Table::new(vec!["count".to_string()]);
+        let mut options = VariableMap::new();
+
+        table.insert(vec![Value::Integer(1)]).unwrap();
+        options
+            .set_value("style", Value::String("ascii".to_string()))
+            .unwrap();
+        options
+            .set_value("align.count", Value::String("left".to_string()))
+            .unwrap();
+
+        let result = RenderTable
+            .run(&Value::List(vec![Value::Table(table), Value::Map(options)]))
+            .unwrap();
+
+        assert!(result.as_string().unwrap().contains("| 1     |"));
+    }
+
+    #[test]
+    fn render_table_unknown_align_column() {
+        let mut table = Table::new(vec!["count".to_string()]);
+        let mut options = VariableMap::new();
+
+        table.insert(vec![Value::Integer(1)]).unwrap();
+        options
+            .set_value("align.total", Value::String("left".to_string()))
+            .unwrap();
+
+        assert!(matches!(
+            RenderTable.run(&Value::List(vec![Value::Table(table), Value::Map(options)])),
+            Err(Error::CustomMessage(_))
+        ));
+    }
+
+    #[test]
+    fn coerce_types() {
+        let mut table = Table::new(vec![
+            "count".to_string(),
+            "score".to_string(),
+            "active".to_string(),
+            "name".to_string(),
+        ]);
+
+        for (count, score, active, name) in [("1", "1.5", "true", "foo"), ("2", "3", "false", "2")]
+        {
+            table
+                .insert(vec![
+                    Value::String(count.to_string()),
+                    Value::String(score.to_string()),
+                    Value::String(active.to_string()),
+                    Value::String(name.to_string()),
+                ])
+                .unwrap();
+        }
+
+        let result = CoerceTypes.run(&Value::Table(table)).unwrap();
+        let rows = result.as_table().unwrap().rows();
+
+        assert_eq!(
+            &vec![
+                Value::Integer(1),
+                Value::Float(1.5),
+                Value::Boolean(true),
+                Value::String("foo".to_string())
+            ],
+            &rows[0]
+        );
+        assert_eq!(
+            &vec![
+                Value::Integer(2),
+                Value::Float(3.0),
+                Value::Boolean(false),
+                Value::String("2".to_string())
+            ],
+            &rows[1]
+        );
+    }
+
+    #[test]
+    fn explode() {
+        let mut table = Table::new(vec!["id".to_string(), "tags".to_string()]);
+        let tags = ["a", "b", "c"]
+            .iter()
+            .map(|tag| Value::String(tag.to_string()))
+            .collect();
+
+        table
+            .insert(vec![Value::Integer(1), Value::List(tags)])
+            .unwrap();
+        table
+            .insert(vec![Value::Integer(2), Value::List(Vec::new())])
+            .unwrap();
+
+        let arguments = Value::List(vec![
+            Value::Table(table.clone()),
+            Value::String("tags".to_string()),
+        ]);
+        let result = Explode.run(&arguments).unwrap();
+        let mut expected = Table::new(vec!["id".to_string(), "tags".to_string()]);
+
+        for tag in ["a", "b", "c"] {
+            expected
+                .insert(vec![Value::Integer(1), Value::String(tag.to_string())])
+                .unwrap();
+        }
+
+        assert_eq!(Value::Table(expected.clone()), result);
+
+        let arguments = Value::List(vec![
+            Value::Table(table),
+            Value::String("tags".to_string()),
+            Value::Boolean(true),
+        ]);
+        let result = Explode.run(&arguments).unwrap();
+
+        expected
+            .insert(vec![Value::Integer(2), Value::Empty])
+            .unwrap();
+
+        assert_eq!(Value::Table(expected), result);
+    }
+
+    #[test]
+    fn sample_sizes() {
+        let list = Value::List((0..5).map(Value::Integer).collect());
+
+        for (count, expected_length) in [(3, 3), (5, 5), (8, 5)] {
+            let result = Sample
+                .run(&Value::List(vec![list.clone(), Value::Integer(count)]))
+                .unwrap();
+            let mut items = result.as_list().unwrap().clone();
+
+            assert_eq!(expected_length, items.len());
+
+            items.sort();
+            items.dedup();
+
+            assert_eq!(expected_length, items.len());
+        }
+
+        let result = Sample
+            .run(&Value::List(vec![
+                list,
+                Value::Integer(8),
+                Value::Boolean(true),
+            ]))
+            .unwrap();
+
+        assert_eq!(8, result.as_list().unwrap().len());
+    }
+
+    #[test]
+    fn sample_count_is_bounded_with_replacement() {
+        let list = Value::List(vec![Value::Integer(1)]);
+        let result = Sample.run(&Value::List(vec![
+            list.clone(),
+            Value::Integer(i64::MAX),
+            Value::Boolean(true),
+        ]));
+
+        assert!(matches!(result, Err(Error::CustomMessage(_))));
+
+        let result = Sample
+            .run(&Value::List(vec![list, Value::Integer(i64::MAX)]))
+            .unwrap();
+
+        assert_eq!(1, result.as_list().unwrap().len());
+    }
+
+    #[test]
+    fn sample_negative_count() {
+        let list = Value::List(vec![Value::Integer(1)]);
+        let result = Sample.run(&Value::List(vec![list, Value::Integer(-1)]));
+
+        assert!(matches!(result, Err(Error::CustomMessage(_))));
+    }
+
+    #[test]
+    fn negative_counts() {
+        let list = Value::List(vec![Value::Integer(1), Value::Integer(2)]);
+        let argument = Value::List(vec![list, Value::Integer(-1)]);
+
+        assert!(Head.run(&argument).is_err());
+        assert!(Tail.run(&argument).is_err());
+        assert!(TopN.run(&argument).is_err());
+        assert!(BottomN.run(&argument).is_err());
+    }
+
+    #[test]
+    fn sample_table_rows() {
+        let table = create_numbered_table(10);
+        let result = Sample
+            .run(&Value::List(vec![Value::Table(table), Value::Integer(4)]))
+            .unwrap();
+
+        assert_eq!(4, result.as_table().unwrap().len());
+    }
+
+    #[test]
+    fn sample_with_seed() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let items: Vec<i64> = (0..100).collect();
+        let first = sample(&items, 10, false, &mut StdRng::seed_from_u64(7));
+        let second = sample(&items, 10, false, &mut StdRng::seed_from_u64(7));
+
+        assert_eq!(first, second);
+        assert_ne!(items[..10].to_vec(), first);
+    }
+
+    #[test]
+    fn compare_large_integers_and_floats() {
+        let big = 1_i64 << 53;
+        let values = [
+            Value::Integer(big + 1),
+            Value::Float(big as f64),
+            Value::Integer(big),
+            Value::Float(-0.0),
+            Value::Integer(0),
+            Value::Float(0.0),
+            Value::Float(f64::NAN),
+            Value::Boolean(true),
+        ];
+
+        assert_eq!(Ordering::Greater, compare_values(&values[0], &values[1]));
+        assert_eq!(Ordering::Equal, compare_values(&values[1], &values[2]));
+        assert_eq!(Ordering::Less, compare_values(&values[3], &values[4]));
+        assert_eq!(Ordering::Equal, compare_values(&values[4], &values[5]));
+        assert_eq!(
+            Ordering::Less,
+            compare_integer_to_float(i64::MAX, 9_223_372_036_854_775_808.0)
+        );
+
+        // The order is transitive across every triple.
+        for a in &values {
+            for b in &values {
+                for c in &values {
+                    if compare_values(a, b) != Ordering::Greater
+                        && compare_values(b, c) != Ordering::Greater
+                    {
+                        assert_ne!(Ordering::Greater, compare_values(a, c), "{a} {b} {c}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn top_n_by_column() {
+        let mut table = Table::new(vec!["name".to_string(), "score".to_string()]);
+
+        for (index, score) in [4, 17, 9, 23, 1, 17, 12, 5].iter().enumerate() {
+            table
+                .insert(vec![
+                    Value::String(format!("player_{index}")),
+                    Value::Integer(*score),
+                ])
+                .unwrap();
+        }
+
+        let result = TopN
+            .run(&Value::List(vec![
+                Value::Table(table.clone()),
+                Value::Integer(3),
+                Value::String("score".to_string()),
+            ]))
+            .unwrap();
+        let names: Vec<&Value> = result
+            .as_table()
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| &row[0])
+            .collect();
+
+        assert_eq!(
+            vec![
+                &Value::String("player_3".to_string()),
+                &Value::String("player_1".to_string()),
+                &Value::String("player_5".to_string()),
+            ],
+            names
+        );
+
+        let result = BottomN
+            .run(&Value::List(vec![
+                Value::Table(table),
+                Value::Integer(2),
+                Value::String("score".to_string()),
+            ]))
+            .unwrap();
+
+        assert_eq!(
+            &Value::String("player_4".to_string()),
+            &result.as_table().unwrap().rows()[0][0]
+        );
+        assert_eq!(
+            &Value::String("player_0".to_string()),
+            &result.as_table().unwrap().rows()[1][0]
+        );
+    }
+
+    #[test]
+    fn top_n_by_function() {
+        let list = Value::List(vec![
+            Value::Integer(3),
+            Value::Integer(-8),
+            Value::Integer(5),
+        ]);
+        let result = TopN
+            .run(&Value::List(vec![
+                list,
+                Value::Integer(5),
+                Value::Function(Function::new("input * input")),
+            ]))
+            .unwrap();
+
+        assert_eq!(
+            Value::List(vec![
+                Value::Integer(-8),
+                Value::Integer(5),
+                Value::Integer(3),
+            ]),
+            result
+        );
+    }
+
+    #[test]
+    fn top_n_mixed_integers_and_floats() {
+        let mut table = Table::new(vec!["score".to_string()]);
+
+        for score in [
+            Value::Integer(2),
+            Value::Float(1.5),
+            Value::Float(2.5),
+            Value::Integer(1),
+        ]
+        .iter()
+        {
+            table.insert(vec![score.clone()]).unwrap();
+        }
+
+        let result = TopN
+            .run(&Value::List(vec![
+                Value::Table(table.clone()),
+                Value::Integer(3),
+                Value::String("score".to_string()),
+            ]))
+            .unwrap();
+        let scores: Vec<&Value> = result
+            .as_table()
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| &row[0])
+            .collect();
+
+        assert_eq!(
+            vec![&Value::Float(2.5), &Value::Integer(2), &Value::Float(1.5)],
+            scores
+        );
+
+        let result = BottomN
+            .run(&Value::List(vec![
+                Value::Table(table),
+                Value::Integer(1),
+                Value::String("score".to_string()),
+            ]))
+            .unwrap();
+
+        assert_eq!(&Value::Integer(1), &result.as_table().unwrap().rows()[0][0]);
+    }
+
+    fn integers(values: &[i64]) -> Value {
+        Value::List(values.iter().copied().map(Value::Integer).collect())
+    }
+
+    #[test]
+    fn rank_distinct() {
+        let list = integers(&[30, 10, 20]);
+
+        assert_eq!(
+            Ok(integers(&[3, 1, 2])),
+            Rank.run(&Value::List(vec![
+                list.clone(),
+                Value::String("asc".to_string())
+            ]))
+        );
+        assert_eq!(
+            Ok(integers(&[1, 3, 2])),
+            Rank.run(&Value::List(vec![list, Value::String("desc".to_string())]))
+        );
+    }
+
+    #[test]
+    fn rank_ties() {
+        let list = integers(&[50, 70, 50, 90, 70, 10]);
+
+        assert_eq!(
+            Ok(integers(&[4, 2, 4, 1, 2, 6])),
+            Rank.run(&Value::List(vec![
+                list.clone(),
+                Value::String("desc".to_string()),
+                Value::String("standard".to_string()),
+            ]))
+        );
+        assert_eq!(
+            Ok(integers(&[3, 2, 3, 1, 2, 4])),
+            Rank.run(&Value::List(vec![
+                list,
+                Value::String("desc".to_string()),
+                Value::String("dense".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn rank_mixed_integers_and_floats() {
+        let list = Value::List(vec![
+            Value::Float(2.5),
+            Value::Integer(2),
+            Value::Float(1.5),
+            Value::Float(2.0),
+        ]);
+
+        assert_eq!(
+            Ok(integers(&[4, 2, 1, 2])),
+            Rank.run(&Value::List(vec![list, Value::String("asc".to_string())]))
+        );
+    }
+
+    #[test]
+    fn push_onto_empty_list() {
+        let result = Push.run(&Value::List(vec![
+            Value::List(Vec::new()),
+            Value::String("whale".to_string()),
+        ]));
+
+        assert_eq!(
+            Ok(Value::List(vec![Value::String("whale".to_string())])),
+            result
+        );
+    }
+
+    #[test]
+    fn pop_single_item() {
+        let result = Pop.run(&Value::List(vec![Value::Integer(1)]));
+
+        assert_eq!(
+            Ok(Value::List(vec![
+                Value::List(Vec::new()),
+                Value::Integer(1)
+            ])),
+            result
+        );
+        assert_eq!(Ok(Value::Empty), Pop.run(&Value::List(Vec::new())));
+    }
+
+    #[test]
+    fn apply_to_column() {
+        let mut doubled = Table::new(vec!["id".to_string()]);
+
+        for id in [0, 2, 4, 6] {
+            doubled.insert(vec![Value::Integer(id)]).unwrap();
+        }
+
+        let result = ApplyToColumn.run(&Value::List(vec![
+            Value::Table(create_numbered_table(4)),
+            Value::String("id".to_string()),
+            Value::Function(Function::new("input * 2")),
+        ]));
+
+        assert_eq!(Ok(Value::Table(doubled)), result);
+    }
 }
@@ -1,6 +1,14 @@
-use std::process::Command;
+use std::{
+    env,
+    io::Read,
+    process::{Child, Command, Stdio},
+    thread,
+};
 
-use crate::{Macro, MacroInfo, Result, Value};
+use crate::{
+    interrupt::{wait_interruptible, INTERRUPTED},
+    Error, Macro, MacroInfo, Result, Value, VariableMap,
+};
 
 pub struct Sh;
 
@@ -14,11 +22,11 @@ impl Macro for Sh {
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
-        let argument = argument.as_string()?;
-
-        Command::new("sh").arg("-c").arg(argument).spawn()?.wait()?;
+        run_shell("sh", argument)
+    }
 
-        Ok(Value::Empty)
+    fn inherits_stdin(&self, argument: &Value) -> bool {
+        argument.is_string()
     }
 }
 
@@ -34,17 +42,14 @@ impl Macro for Bash {
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
-        let argument = argument.as_string()?;
-
-        Command::new("bash")
-            .arg("-c")
-            .arg(argument)
-            .spawn()?
-            .wait()?;
+        run_shell("bash", argument)
+    }
 
-        Ok(Value::Empty)
+    fn inherits_stdin(&self, argument: &Value) -> bool {
+        argument.is_string()
     }
 }
+
 pub struct Fish;
 
 impl Macro for Fish {
@@ -57,15 +62,11 @@ impl Macro for Fish {
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
-        let argument = argument.as_string()?;
-
-        Command::new("fish")
-            .arg("-c")
-            .arg(argument)
-            .spawn()?
-            .wait()?;
+        run_shell("fish", argument)
+    }
 
-        Ok(Value::Empty)
+    fn inherits_stdin(&self, argument: &Value) -> bool {
+        argument.is_string()
     }
 }
 
@@ -81,15 +82,11 @@ impl Macro for Zsh {
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
-        let argument = argument.as_string()?;
-
-        Command::new("zsh")
-            .arg("-c")
-            .arg(argument)
-            .spawn()?
-            .wait()?;
+        run_shell("zsh", argument)
+    }
 
-        Ok(Value::Empty)
+    fn inherits_stdin(&self, argument: &Value) -> bool {
+        argument.is_string()
     }
 }
 
@@ -111,4 +108,314 @@ impl Macro for Raw {
 
         Ok(Value::Empty)
     }
+
+    fn inherits_stdin(&self, _argument: &Value) -> bool {
+        true
+    }
+}
+
+pub struct ExecPipeline;
+
+impl Macro for ExecPipeline {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "exec_pipeline",
+            description: "Run a list of shell commands, piping each one's output into the next, and return the final output.",
+            group: "command",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let commands = argument.as_list()?;
+
+        Error::expected_minimum_function_argument_amount(
+            self.info().identifier,
+            commands.len(),
+            1,
+        )?;
+
+        let mut children: Vec<Child> = Vec::with_capacity(commands.len());
+
+        for command in commands {
+            let command = command.as_string()?;
+            let stdin = match children.last_mut().and_then(|child| child.stdout.take()) {
+                Some(stdout) => Stdio::from(stdout),
+                None => Stdio::null(),
+            };
+            let child = Command::new(default_shell())
+                .arg("-c")
+                .arg(command)
+                .stdin(stdin)
+                .stdout(Stdio::piped())
+                .spawn()?;
+
+            children.push(child);
+        }
+
+        let last = children.pop().unwrap();
+        let output = last.wait_with_output()?;
+
+        for mut child in children {
+            child.wait()?;
+        }
+
+        Ok(Value::String(
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        ))
+    }
+}
+
+pub struct RunWithEnv;
+
+impl Macro for RunWithEnv {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "run_with_env",
+            description: "Run a shell command with extra environment variables from a map and return its output.",
+            group: "command",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let variables = argument[1].as_map()?;
+        let mut command = Command::new(default_shell());
+
+        command.arg("-c").arg(argument[0].as_string()?);
+
+        for (key, value) in variables.inner() {
+            command.env(key, value.to_string());
+        }
+
+        let output = command.stderr(Stdio::inherit()).output()?;
+
+        Ok(Value::String(
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        ))
+    }
+}
+
+pub struct Start;
+
+impl Macro for Start {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "start",
+            description: "Start a shell command in the background and return its process ID.",
+            group: "command",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_string()?;
+        let mut command = Command::new(default_shell());
+
+        command
+            .arg("-c")
+            .arg(argument)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        // A new process group keeps Ctrl-C in the terminal from reaching the child.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+        let mut child = command.spawn()?;
+        let id = child.id();
+
+        // Reap the child when it exits so that it doesn't linger as a zombie process.
+        thread::spawn(move || child.wait());
+
+        Ok(Value::Integer(id as i64))
+    }
+}
+
+pub struct DefaultShell;
+
+impl Macro for DefaultShell {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "default_shell",
+            description: "Return the shell used to run commands, taken from $SHELL if it is set.",
+            group: "command",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        argument.as_empty()?;
+
+        Ok(Value::String(default_shell()))
+    }
+}
+
+/// Run a command with the given shell. A plain string runs interactively and returns
+/// nothing. A `[command, allow_failure]` list captures the output and returns a map of
+/// `stdout`, `stderr` and `exit_code`. A non-zero exit is an error unless `allow_failure`
+/// is true.
+fn run_shell(shell: &str, argument: &Value) -> Result<Value> {
+    if let Ok(command) = argument.as_string() {
+        let mut child = Command::new(shell).arg("-c").arg(command).spawn()?;
+
+        wait_interruptible(&mut child, &INTERRUPTED)?;
+
+        return Ok(Value::Empty);
+    }
+
+    let argument = argument.as_fixed_len_list(2)?;
+    let command = argument[0].as_string()?;
+    let allow_failure = argument[1].as_boolean()?;
+    let mut child = Command::new(shell)
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout_reader = read_in_background(child.stdout.take());
+    let stderr_reader = read_in_background(child.stderr.take());
+    let status = wait_interruptible(&mut child, &INTERRUPTED)?;
+    let stdout = String::from_utf8_lossy(&stdout_reader.join().unwrap_or_default()).to_string();
+    let stderr = String::from_utf8_lossy(&stderr_reader.join().unwrap_or_default()).to_string();
+
+    if !status.success() && !allow_failure {
+        return Err(Error::MacroFailure(format!(
+            "\"{command}\" failed with {}: {}",
+            status,
+            stderr.trim_end()
+        )));
+    }
+
+    let exit_code = match status.code() {
+        Some(code) => Value::Integer(code as i64),
+        None => Value::Empty,
+    };
+    let mut result = VariableMap::new();
+
+    result.set_value("stdout", Value::String(stdout))?;
+    result.set_value("stderr", Value::String(stderr))?;
+    result.set_value("exit_code", exit_code)?;
+
+    Ok(Value::Map(result))
+}
+
+/// Read a child's output pipe on a separate thread so that the child can't block on a full
+/// pipe while its parent waits for it to exit.
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+
+        buffer
+    })
+}
+
+/// The shell that runs commands for macros that are not tied to a specific shell.
+pub(crate) fn default_shell() -> String {
+    select_shell(env::var("SHELL").ok())
+}
+
+fn select_shell(shell_variable: Option<String>) -> String {
+    match shell_variable {
+        Some(shell) if !shell.is_empty() => shell,
+        _ if cfg!(windows) => "powershell".to_string(),
+        _ => "sh".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_pipeline() {
+        let commands = Value::List(vec![
+            Value::String("echo hiya".to_string()),
+            Value::String("tr a-z A-Z".to_string()),
+            Value::String("rev".to_string()),
+        ]);
+
+        assert_eq!(
+            Ok(Value::String("AYIH\n".to_string())),
+            ExecPipeline.run(&commands)
+        );
+    }
+
+    #[test]
+    fn run_with_env() {
+        let mut variables = VariableMap::new();
+
+        variables
+            .set_value("WHALE_RUN_WITH_ENV", Value::String("hiya".to_string()))
+            .unwrap();
+
+        let result = RunWithEnv.run(&Value::List(vec![
+            Value::String("echo $WHALE_RUN_WITH_ENV".to_string()),
+            Value::Map(variables),
+        ]));
+
+        assert_eq!(Ok(Value::String("hiya\n".to_string())), result);
+        assert!(std::env::var("WHALE_RUN_WITH_ENV").is_err());
+    }
+
+    #[test]
+    fn start() {
+        let pid = Start
+            .run(&Value::String("sleep 1".to_string()))
+            .unwrap()
+            .as_int()
+            .unwrap();
+
+        assert!(pid > 0);
+    }
+
+    #[test]
+    fn select_shell_from_variable() {
+        assert_eq!("/bin/zsh", select_shell(Some("/bin/zsh".to_string())));
+    }
+
+    #[test]
+    fn select_shell_without_variable() {
+        let fallback = if cfg!(windows) { "powershell" } else { "sh" };
+
+        assert_eq!(fallback, select_shell(None));
+        assert_eq!(fallback, select_shell(Some(String::new())));
+    }
+
+    #[test]
+    fn sh_allow_failure() {
+        let result = Sh
+            .run(&Value::List(vec![
+                Value::String("echo hiya; echo oops >&2; exit 3".to_string()),
+                Value::Boolean(true),
+            ]))
+            .unwrap();
+        let result = result.as_map().unwrap();
+
+        assert_eq!(
+            result.get_value("exit_code").unwrap(),
+            Some(Value::Integer(3))
+        );
+        assert_eq!(
+            result.get_value("stdout").unwrap(),
+            Some(Value::String("hiya\n".to_string()))
+        );
+        assert_eq!(
+            result.get_value("stderr").unwrap(),
+            Some(Value::String("oops\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn sh_failure() {
+        let result = Sh.run(&Value::List(vec![
+            Value::String("exit 3".to_string()),
+            Value::Boolean(false),
+        ]));
+
+        assert!(matches!(result, Err(Error::MacroFailure(_))));
+    }
 }
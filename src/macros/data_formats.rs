@@ -1,6 +1,8 @@
 //! Convert values to and from data formats like JSON and TOML.
 
-use crate::{Macro, MacroInfo, Result, Table, Value};
+use std::fs;
+
+use crate::{Error, Macro, MacroInfo, Result, Table, Value};
 
 pub struct FromJson;
 
@@ -39,50 +41,148 @@ impl Macro for ToJson {
     }
 }
 
+pub struct ToJsonPretty;
+
+impl Macro for ToJsonPretty {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "to_json_pretty",
+            description: "Create an indented, multi-line JSON string from a whale value.",
+            group: "data",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let json = serde_json::to_string_pretty(argument)?;
+
+        Ok(Value::String(json))
+    }
+}
+
 pub struct FromCsv;
 
 impl Macro for FromCsv {
     fn info(&self) -> MacroInfo<'static> {
         MacroInfo {
             identifier: "from_csv",
-            description: "Create a whale value from a CSV string.",
+            description: "Create a whale value from a CSV string. The delimiter can be a comma, tab, semicolon or pipe.",
             group: "data",
         }
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
         let csv = argument.as_string()?;
-        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let table = csv_to_table(csv, sniff_delimiter(csv), true)?;
 
-        let headers = reader
-            .headers()?
+        Ok(Value::Table(table))
+    }
+}
+
+/// Parses CSV text into a table, turning numeric cells into integers or floats. Without
+/// headers, the columns are named `column_0`, `column_1` and so on.
+fn csv_to_table(csv: &str, delimiter: u8, has_headers: bool) -> Result<Table> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_headers)
+        .from_reader(csv.as_bytes());
+    // Without a header row, the csv crate returns the first record here but does not consume it.
+    let headers = reader
+        .headers()?
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            if has_headers {
+                header.trim().trim_matches('"').to_string()
+            } else {
+                format!("column_{index}")
+            }
+        })
+        .collect();
+    let mut table = Table::new(headers);
+
+    for result in reader.records() {
+        let row = result?
             .iter()
-            .map(|header| header.trim().trim_matches('"').to_string())
+            .map(|column| {
+                let column = column.trim().trim_matches('"').trim_matches('\'');
+
+                if column.is_empty() {
+                    Value::Empty
+                } else if let Ok(integer) = column.parse::<i64>() {
+                    Value::Integer(integer)
+                } else if let Some(float) = parse_decimal(column) {
+                    Value::Float(float)
+                } else {
+                    Value::String(column.to_string())
+                }
+            })
             .collect();
 
-        let mut table = Table::new(headers);
-
-        for result in reader.records() {
-            let row = result?
-                .iter()
-                .map(|column| {
-                    let column = column.trim().trim_matches('"').trim_matches('\'');
-
-                    if let Ok(integer) = column.parse::<i64>() {
-                        Value::Integer(integer)
-                    } else if let Ok(float) = column.parse::<f64>() {
-                        Value::Float(float)
-                    } else {
-                        Value::String(column.to_string())
-                    }
-                })
-                .collect();
-
-            table.insert(row)?;
+        table.insert(row)?;
+    }
+
+    Ok(table)
+}
+
+/// Parses text written as a decimal number, like `2.5` or `-1e3`. Words that Rust would
+/// also read as floats, like `nan` and `inf`, are left alone.
+pub(super) fn parse_decimal(text: &str) -> Option<f64> {
+    let is_decimal = text.chars().any(|character| character.is_ascii_digit())
+        && text
+            .chars()
+            .all(|character| character.is_ascii_digit() || "+-.eE".contains(character));
+
+    if is_decimal {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Guesses the delimiter of CSV text by sampling its first lines. The candidate that appears
+/// the same number of times on every sampled line wins, preferring the most frequent one.
+/// Falls back to a comma when nothing is consistent.
+fn sniff_delimiter(csv: &str) -> u8 {
+    const CANDIDATES: [u8; 4] = [b',', b'\t', b';', b'|'];
+    const SAMPLE_LINES: usize = 5;
+
+    let lines: Vec<&str> = csv
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SAMPLE_LINES)
+        .collect();
+    let mut best = (b',', 0);
+
+    for candidate in CANDIDATES {
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|line| count_unquoted(line, candidate))
+            .collect();
+        let first = counts.first().copied().unwrap_or(0);
+        let is_consistent = first > 0 && counts.iter().all(|count| *count == first);
+
+        if is_consistent && first > best.1 {
+            best = (candidate, first);
         }
+    }
 
-        Ok(Value::Table(table))
+    best.0
+}
+
+/// Counts occurrences of the byte outside of double-quoted fields.
+fn count_unquoted(line: &str, delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+
+    for byte in line.bytes() {
+        if byte == b'"' {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            count += 1;
+        }
     }
+
+    count
 }
 
 pub struct ToCsv;
@@ -145,3 +245,398 @@ impl Macro for ToCsv {
         ))
     }
 }
+
+pub struct ReadCsv;
+
+impl Macro for ReadCsv {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "read_csv",
+            description: "Read a CSV file into a table. Pass a map of {path, delimiter, has_headers} to override the detected delimiter or to read a file without a header row.",
+            group: "data",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let (path, delimiter, has_headers) = match argument {
+            Value::Map(options) => {
+                let path = options
+                    .get_value("path")?
+                    .ok_or_else(|| Error::MacroFailure("read_csv needs a path".to_string()))?
+                    .as_string()?
+                    .clone();
+                let delimiter = match options.get_value("delimiter")? {
+                    Some(delimiter) => Some(csv_delimiter(delimiter.as_string()?)?),
+                    None => None,
+                };
+                let has_headers = match options.get_value("has_headers")? {
+                    Some(has_headers) => has_headers.as_boolean()?,
+                    None => true,
+                };
+
+                (path, delimiter, has_headers)
+            }
+            _ => (argument.as_string()?.clone(), None, true),
+        };
+        let csv = fs::read_to_string(path)?;
+        let delimiter = delimiter.unwrap_or_else(|| sniff_delimiter(&csv));
+        let table = csv_to_table(&csv, delimiter, has_headers)?;
+
+        Ok(Value::Table(table))
+    }
+}
+
+fn csv_delimiter(delimiter: &str) -> Result<u8> {
+    match delimiter.as_bytes() {
+        [byte] => Ok(*byte),
+        _ => Err(Error::MacroFailure(format!(
+            "The CSV delimiter must be a single ASCII character, not \"{delimiter}\"."
+        ))),
+    }
+}
+
+pub struct WriteCsv;
+
+impl Macro for WriteCsv {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "write_csv",
+            description: "Write a table to a file as comma-separated values.",
+            group: "data",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let table = argument[0].as_table()?;
+        let path = argument[1].as_string()?;
+        let mut writer = csv::Writer::from_path(path)?;
+
+        writer.write_record(table.column_names())?;
+
+        for row in table.rows() {
+            let record = row.iter().map(csv_cell).collect::<Result<Vec<String>>>()?;
+
+            writer.write_record(record)?;
+        }
+
+        writer.flush()?;
+
+        Ok(Value::Empty)
+    }
+}
+
+/// Writes a cell so that read_csv reads back the same value. Empty cells stay blank and
+/// whole floats keep their decimal point. Collections can't be written as a single cell.
+fn csv_cell(value: &Value) -> Result<String> {
+    match value {
+        Value::Empty => Ok(String::new()),
+        Value::Float(float) => Ok(format!("{float:?}")),
+        Value::String(_) | Value::Integer(_) | Value::Boolean(_) | Value::Time(_) => {
+            Ok(value.to_string())
+        }
+        _ => Err(Error::MacroFailure(format!(
+            "Cannot write {value} as a single CSV cell."
+        ))),
+    }
+}
+
+pub struct ReadJson;
+
+impl Macro for ReadJson {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "read_json",
+            description: "Read a JSON file into a whale value.",
+            group: "data",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let path = argument.as_string()?;
+        let json = fs::read_to_string(path)?;
+
+        FromJson.run(&Value::String(json))
+    }
+}
+
+pub struct WriteJson;
+
+impl Macro for WriteJson {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "write_json",
+            description:
+                "Write a value to a file as JSON. Pass true as a third argument to pretty-print it.",
+            group: "data",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_list()?;
+
+        Error::expected_minimum_function_argument_amount(
+            self.info().identifier,
+            argument.len(),
+            2,
+        )?;
+
+        let value = &argument[0];
+        let path = argument[1].as_string()?;
+        let pretty = match argument.get(2) {
+            Some(pretty) => pretty.as_boolean()?,
+            None => false,
+        };
+        let json = if pretty {
+            serde_json::to_string_pretty(value)?
+        } else {
+            serde_json::to_string(value)?
+        };
+
+        fs::write(path, json)?;
+
+        Ok(Value::Empty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::VariableMap;
+
+    use super::*;
+
+    #[test]
+    fn to_json_pretty() {
+        let list = Value::List(vec![Value::Integer(1), Value::Boolean(true)]);
+        let compact = ToJson.run(&list).unwrap();
+        let pretty = ToJsonPretty.run(&list).unwrap();
+
+        assert_eq!(Value::String("[1,true]".to_string()), compact);
+        assert!(!compact.as_string().unwrap().contains('\n'));
+        assert!(pretty.as_string().unwrap().contains('\n'));
+        assert_eq!(Ok(list), FromJson.run(&pretty));
+    }
+
+    #[test]
+    fn sniff_delimiters() {
+        assert_eq!(b',', sniff_delimiter("id,name\n1,foo\n2,bar\n"));
+        assert_eq!(b'\t', sniff_delimiter("id\tname\n1\tfoo, jr\n2\tbar\n"));
+        assert_eq!(b';', sniff_delimiter("id;price\n1;\"1,50\"\n2;2,00\n"));
+        assert_eq!(b'|', sniff_delimiter("id|name\n1|foo\n"));
+        assert_eq!(b',', sniff_delimiter("just one column\n"));
+    }
+
+    #[test]
+    fn from_csv_with_tabs() {
+        let result = FromCsv.run(&Value::String("id\tname\n1\tfoo\n".to_string()));
+        let mut table = Table::new(vec!["id".to_string(), "name".to_string()]);
+
+        table
+            .insert(vec![Value::Integer(1), Value::String("foo".to_string())])
+            .unwrap();
+
+        assert_eq!(Ok(Value::Table(table)), result);
+    }
+
+    #[test]
+    fn csv_file_round_trip() {
+        let path = Value::String("./target/csv_file_round_trip.csv".to_string());
+        let mut table = Table::new(vec!["name".to_string(), "count".to_string()]);
+
+        table
+            .insert(vec![Value::String("whale".to_string()), Value::Integer(1)])
+            .unwrap();
+        table
+            .insert(vec![Value::String("shark".to_string()), Value::Float(2.5)])
+            .unwrap();
+
+        WriteCsv
+            .run(&Value::List(vec![
+                Value::Table(table.clone()),
+                path.clone(),
+            ]))
+            .unwrap();
+
+        assert_eq!(Value::Table(table), ReadCsv.run(&path).unwrap());
+    }
+
+    #[test]
+    fn write_csv_quotes_values() {
+        let path = Value::String("./target/write_csv_quotes_values.csv".to_string());
+        let mut table = Table::new(vec!["name".to_string(), "note".to_string()]);
+
+        table
+            .insert(vec![
+                Value::String("Smith, Jo".to_string()),
+                Value::String("first line\nsecond line".to_string()),
+            ])
+            .unwrap();
+
+        WriteCsv
+            .run(&Value::List(vec![
+                Value::Table(table.clone()),
+                path.clone(),
+            ]))
+            .unwrap();
+
+        assert_eq!(Value::Table(table), ReadCsv.run(&path).unwrap());
+        assert!(WriteCsv
+            .run(&Value::List(vec![Value::Integer(1), path]))
+            .is_err());
+    }
+
+    #[test]
+    fn write_csv_round_trip() {
+        let path = Value::String("./target/write_csv_round_trip.csv".to_string());
+        let mut table = Table::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+
+        table
+            .insert(vec![Value::Empty, Value::Float(2.0), Value::Integer(3)])
+            .unwrap();
+
+        WriteCsv
+            .run(&Value::List(vec![
+                Value::Table(table.clone()),
+                path.clone(),
+            ]))
+            .unwrap();
+
+        assert_eq!(Value::Table(table), ReadCsv.run(&path).unwrap());
+    }
+
+    #[test]
+    fn write_csv_nested_value() {
+        let path = Value::String("./target/write_csv_nested_value.csv".to_string());
+        let mut table = Table::new(vec!["list".to_string()]);
+
+        table
+            .insert(vec![Value::List(vec![Value::Integer(1)])])
+            .unwrap();
+
+        assert!(matches!(
+            WriteCsv.run(&Value::List(vec![Value::Table(table), path])),
+            Err(Error::MacroFailure(_))
+        ));
+    }
+
+    #[test]
+    fn json_file_round_trip() {
+        let mut inner = VariableMap::new();
+        let mut map = VariableMap::new();
+
+        inner
+            .set_value("tags", Value::List(vec![Value::String("big".to_string())]))
+            .unwrap();
+        map.set_value("name", Value::String("whale".to_string()))
+            .unwrap();
+        map.set_value("size", Value::Float(30.5)).unwrap();
+        map.set_value("details", Value::Map(inner)).unwrap();
+        map.set_value("nickname", Value::Empty).unwrap();
+
+        for (file, pretty) in [("compact", false), ("pretty", true)] {
+            let path = Value::String(format!("./target/json_file_round_trip_{file}.json"));
+
+            WriteJson
+                .run(&Value::List(vec![
+                    Value::Map(map.clone()),
+                    path.clone(),
+                    Value::Boolean(pretty),
+                ]))
+                .unwrap();
+
+            assert_eq!(Value::Map(map.clone()), ReadJson.run(&path).unwrap());
+        }
+    }
+
+    #[test]
+    fn read_csv_with_headers() {
+        let path = "./target/read_csv_with_headers.csv";
+
+        fs::write(path, "name;count\nwhale;1\nshark;2.5\n").unwrap();
+
+        let mut options = VariableMap::new();
+
+        options
+            .set_value("path", Value::String(path.to_string()))
+            .unwrap();
+        options
+            .set_value("delimiter", Value::String(";".to_string()))
+            .unwrap();
+
+        let mut table = Table::new(vec!["name".to_string(), "count".to_string()]);
+
+        table
+            .insert(vec![Value::String("whale".to_string()), Value::Integer(1)])
+            .unwrap();
+        table
+            .insert(vec![Value::String("shark".to_string()), Value::Float(2.5)])
+            .unwrap();
+
+        assert_eq!(Ok(Value::Table(table)), ReadCsv.run(&Value::Map(options)));
+    }
+
+    #[test]
+    fn read_csv_and_coerce_types() {
+        let path = "./target/read_csv_and_coerce_types.csv";
+
+        fs::write(path, "count,score,word\n1,1,nan\n2,2.5,inf\n").unwrap();
+
+        let table = ReadCsv.run(&Value::String(path.to_string())).unwrap();
+        let coerced = crate::macros::collections::CoerceTypes.run(&table).unwrap();
+        let rows = coerced.as_table().unwrap().rows();
+
+        assert_eq!(
+            &vec![
+                Value::Integer(1),
+                Value::Float(1.0),
+                Value::String("nan".to_string())
+            ],
+            &rows[0]
+        );
+        assert_eq!(
+            &vec![
+                Value::Integer(2),
+                Value::Float(2.5),
+                Value::String("inf".to_string())
+            ],
+            &rows[1]
+        );
+    }
+
+    #[test]
+    fn parse_decimals() {
+        assert_eq!(Some(2.5), parse_decimal("2.5"));
+        assert_eq!(Some(-1000.0), parse_decimal("-1e3"));
+        assert_eq!(None, parse_decimal("nan"));
+        assert_eq!(None, parse_decimal("inf"));
+        assert_eq!(None, parse_decimal("-infinity"));
+        assert_eq!(None, parse_decimal("."));
+    }
+
+    #[test]
+    fn read_csv_without_headers() {
+        let path = "./target/read_csv_without_headers.csv";
+
+        fs::write(path, "whale,1\nshark,2\n").unwrap();
+
+        let mut options = VariableMap::new();
+
+        options
+            .set_value("path", Value::String(path.to_string()))
+            .unwrap();
+        options
+            .set_value("has_headers", Value::Boolean(false))
+            .unwrap();
+
+        let mut table = Table::new(vec!["column_0".to_string(), "column_1".to_string()]);
+
+        table
+            .insert(vec![Value::String("whale".to_string()), Value::Integer(1)])
+            .unwrap();
+        table
+            .insert(vec![Value::String("shark".to_string()), Value::Integer(2)])
+            .unwrap();
+
+        assert_eq!(Ok(Value::Table(table)), ReadCsv.run(&Value::Map(options)));
+    }
+}
@@ -2,6 +2,7 @@ use std::process::Command;
 
 use sysinfo::{DiskExt, System, SystemExt};
 
+use super::command::default_shell;
 use crate::{Macro, MacroInfo, Result, Table, Value};
 
 pub struct ListDisks;
@@ -110,7 +111,7 @@ impl Macro for Partition {
             "sudo parted {path} mklabel {label} mkpart {name} {filesystem} {range_start} {range_end}"
         );
 
-        Command::new("fish")
+        Command::new(default_shell())
             .arg("-c")
             .arg(&script)
             .spawn()?
@@ -0,0 +1,152 @@
+//! Macros for encoding and decoding text.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{Error, Macro, MacroInfo, Result, Value};
+
+pub struct Base64Encode;
+
+impl Macro for Base64Encode {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "base64_encode",
+            description: "Encode a string as base64.",
+            group: "encoding",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let string = argument.as_string()?;
+
+        Ok(Value::String(STANDARD.encode(string)))
+    }
+}
+
+pub struct Base64Decode;
+
+impl Macro for Base64Decode {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "base64_decode",
+            description: "Decode base64 into a UTF-8 string.",
+            group: "encoding",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let encoded = argument.as_string()?;
+        let bytes = STANDARD.decode(encoded)?;
+        let string = String::from_utf8(bytes)?;
+
+        Ok(Value::String(string))
+    }
+}
+
+pub struct UrlEncode;
+
+impl Macro for UrlEncode {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "url_encode",
+            description: "Percent-encode a string for use in a URL.",
+            group: "encoding",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let string = argument.as_string()?;
+
+        Ok(Value::String(urlencoding::encode(string).into_owned()))
+    }
+}
+
+pub struct UrlDecode;
+
+impl Macro for UrlDecode {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "url_decode",
+            description: "Decode a percent-encoded string.",
+            group: "encoding",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let encoded = argument.as_string()?;
+        let mut bytes = encoded.bytes();
+
+        // The decoder passes malformed escapes through unchanged, so check them first.
+        while let Some(byte) = bytes.next() {
+            if byte == b'%' {
+                let is_valid = matches!(
+                    (bytes.next(), bytes.next()),
+                    (Some(high), Some(low)) if high.is_ascii_hexdigit() && low.is_ascii_hexdigit()
+                );
+
+                if !is_valid {
+                    return Err(Error::MacroFailure(format!(
+                        "{encoded} contains an invalid percent-encoded sequence."
+                    )));
+                }
+            }
+        }
+
+        let decoded = urlencoding::decode(encoded)?;
+
+        Ok(Value::String(decoded.into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trip() {
+        let text = Value::String("hiya, whale 🐋".to_string());
+        let encoded = Base64Encode.run(&text).unwrap();
+
+        assert_eq!(
+            Value::String("aGl5YSwgd2hhbGUg8J+Qiw==".to_string()),
+            encoded
+        );
+        assert_eq!(text, Base64Decode.run(&encoded).unwrap());
+    }
+
+    #[test]
+    fn base64_decode_invalid() {
+        let not_base64 = Value::String("not base64!".to_string());
+        let not_utf8 = Value::String("/w==".to_string());
+
+        assert!(matches!(
+            Base64Decode.run(&not_base64),
+            Err(Error::MacroFailure(_))
+        ));
+        assert!(matches!(
+            Base64Decode.run(&not_utf8),
+            Err(Error::MacroFailure(_))
+        ));
+    }
+
+    #[test]
+    fn url_round_trip() {
+        let text = Value::String("whale & friends = 100% fun?".to_string());
+        let encoded = UrlEncode.run(&text).unwrap();
+
+        assert_eq!(
+            Value::String("whale%20%26%20friends%20%3D%20100%25%20fun%3F".to_string()),
+            encoded
+        );
+        assert_eq!(text, UrlDecode.run(&encoded).unwrap());
+    }
+
+    #[test]
+    fn url_decode_invalid() {
+        for invalid in ["100%", "%zz", "%4"] {
+            assert!(matches!(
+                UrlDecode.run(&Value::String(invalid.to_string())),
+                Err(Error::MacroFailure(_))
+            ));
+        }
+    }
+}
@@ -1,12 +1,27 @@
 //! Tools for files and directories.
 
 use std::{
-    fs::{self, OpenOptions},
-    io::{Read, Write as IoWrite},
+    collections::BTreeSet,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Read, Write as IoWrite},
     path::PathBuf,
+    sync::{
+        atomic::AtomicBool,
+        mpsc::{self, RecvTimeoutError},
+    },
+    time::Duration,
 };
 
-use crate::{Error, Macro, MacroInfo, Result, Table, Time, Value, ValueType};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use notify::{EventKind, RecursiveMode, Watcher};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{
+    interrupt::{check_interrupt, INTERRUPTED},
+    Error, Macro, MacroInfo, Result, Table, Time, Value, ValueType, VariableMap,
+};
 
 pub struct Append;
 
@@ -31,6 +46,31 @@ impl Macro for Append {
     }
 }
 
+pub struct Cat;
+
+impl Macro for Cat {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "cat",
+            description: "Read a list of files and join their contents in order.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let paths = argument.as_list()?;
+        let mut contents = String::new();
+
+        for path in paths {
+            let path = path.as_string()?;
+
+            contents.push_str(&fs::read_to_string(path)?);
+        }
+
+        Ok(Value::String(contents))
+    }
+}
+
 pub struct CreateDir;
 
 impl Macro for CreateDir {
@@ -50,6 +90,61 @@ impl Macro for CreateDir {
     }
 }
 
+pub struct EachLine;
+
+impl Macro for EachLine {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "each_line",
+            description: "Run a function on each line of a file without reading the whole file. Results that are not empty are collected into a list.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        self.run_with_context(argument, &mut VariableMap::new())
+    }
+
+    fn run_with_context(&self, argument: &Value, context: &mut VariableMap) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let path = argument[0].as_string()?;
+        let function = argument[1].as_function()?;
+        let reader = BufReader::new(File::open(path)?);
+        let mut line_context = context.clone();
+        let mut results = Vec::new();
+
+        for line in reader.lines() {
+            line_context.set_value("input", Value::String(line?))?;
+
+            let result = function.run_with_context(&mut line_context)?;
+
+            if !result.is_empty() {
+                results.push(result);
+            }
+        }
+
+        Ok(Value::List(results))
+    }
+}
+
+pub struct FileExists;
+
+impl Macro for FileExists {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "file_exists",
+            description: "Check whether a file or directory exists.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let path = argument.as_string()?;
+
+        Ok(Value::Boolean(PathBuf::from(path).exists()))
+    }
+}
+
 pub struct FileMetadata;
 
 impl Macro for FileMetadata {
@@ -162,6 +257,25 @@ impl Macro for ReadDir {
     }
 }
 
+pub struct ReadBytes;
+
+impl Macro for ReadBytes {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "read_bytes",
+            description: "Read a file's raw bytes as a base64 string. Works on binary files.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let path = argument.as_string()?;
+        let bytes = fs::read(path)?;
+
+        Ok(Value::String(STANDARD.encode(bytes)))
+    }
+}
+
 pub struct ReadFile;
 
 impl Macro for ReadFile {
@@ -193,19 +307,293 @@ impl Macro for RemoveDir {
     fn info(&self) -> MacroInfo<'static> {
         MacroInfo {
             identifier: "remove_dir",
-            description: "Remove directories.",
+            description: "Remove a directory and everything in it.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let path = PathBuf::from(argument.as_string()?);
+
+        if !path.exists() {
+            return Err(Error::MacroFailure(format!(
+                "Cannot remove {} because it does not exist.",
+                path.display()
+            )));
+        }
+
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        } else {
+            fs::remove_file(path)?;
+        }
+
+        Ok(Value::Empty)
+    }
+}
+
+pub struct HashFile;
+
+impl Macro for HashFile {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "hash_file",
+            description: "Get the hex digest of a file using sha256 (the default), sha512 or md5.",
             group: "filesystem",
         }
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
+        let (path, algorithm) = if let Ok(path) = argument.as_string() {
+            (path, "sha256")
+        } else {
+            let argument = argument.as_list()?;
+
+            Error::expected_minimum_function_argument_amount(
+                self.info().identifier,
+                argument.len(),
+                1,
+            )?;
+
+            let path = argument[0].as_string()?;
+            let algorithm = match argument.get(1) {
+                Some(value) => value.as_string()?.as_str(),
+                None => "sha256",
+            };
+
+            (path, algorithm)
+        };
+        let file = File::open(path)?;
+
+        let digest = match algorithm {
+            "sha256" => {
+                let mut hasher = Sha256::new();
+
+                read_chunks(file, |chunk| hasher.update(chunk))?;
+
+                format!("{:x}", hasher.finalize())
+            }
+            "sha512" => {
+                let mut hasher = Sha512::new();
+
+                read_chunks(file, |chunk| hasher.update(chunk))?;
+
+                format!("{:x}", hasher.finalize())
+            }
+            "md5" => {
+                let mut context = md5::Context::new();
+
+                read_chunks(file, |chunk| context.consume(chunk))?;
+
+                format!("{:x}", context.compute())
+            }
+            _ => {
+                return Err(Error::CustomMessage(format!(
+                    "Unknown hash algorithm {algorithm}. Use sha256, sha512 or md5."
+                )))
+            }
+        };
+
+        Ok(Value::String(digest))
+    }
+}
+
+/// Read a file piece by piece so that large files are never held in memory.
+fn read_chunks(mut file: File, mut handle_chunk: impl FnMut(&[u8])) -> Result<()> {
+    let mut buffer = [0; 8192];
+
+    loop {
+        let length = file.read(&mut buffer)?;
+
+        if length == 0 {
+            return Ok(());
+        }
+
+        handle_chunk(&buffer[..length]);
+    }
+}
+
+pub struct IsDir;
+
+impl Macro for IsDir {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "is_dir",
+            description: "Check whether a path is a directory.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let path = argument.as_string()?;
+
+        Ok(Value::Boolean(PathBuf::from(path).is_dir()))
+    }
+}
+
+pub struct LoadContext;
+
+impl Macro for LoadContext {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "load_context",
+            description: "Load variables from a JSON file written by save_context.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, _argument: &Value) -> Result<Value> {
+        Err(Error::ContextNotMutable)
+    }
+
+    fn run_with_context(&self, argument: &Value, context: &mut VariableMap) -> Result<Value> {
+        let path = argument.as_string()?;
+        let file_contents = fs::read_to_string(path)?;
+        let saved: SavedContext = serde_json::from_str(&file_contents)?;
+
+        // Load into a copy so that a constant in the way leaves the context untouched.
+        let mut updated = context.clone();
+
+        for (key, value) in saved.variables.inner() {
+            if saved.constants.contains(key) {
+                updated.set_constant(key, value.clone())?;
+            } else {
+                updated.set_value(key, value.clone())?;
+            }
+        }
+
+        *context = updated;
+
+        Ok(Value::Empty)
+    }
+}
+
+/// The file written by `save_context` and read by `load_context`.
+#[derive(Serialize, Deserialize)]
+struct SavedContext {
+    variables: VariableMap,
+    #[serde(default)]
+    constants: BTreeSet<String>,
+}
+
+pub struct SaveContext;
+
+impl Macro for SaveContext {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "save_context",
+            description: "Save all current variables and constants to a JSON file.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, _argument: &Value) -> Result<Value> {
+        Err(Error::ContextNotMutable)
+    }
+
+    fn run_with_read_context(&self, argument: &Value, context: &VariableMap) -> Result<Value> {
         let path = argument.as_string()?;
-        fs::remove_file(path)?;
+        let saved = SavedContext {
+            variables: context.clone(),
+            constants: context.constants().cloned().collect(),
+        };
+        let json = serde_json::to_string(&saved)?;
+
+        fs::write(path, json)?;
 
         Ok(Value::Empty)
     }
 }
 
+pub struct Glob;
+
+impl Macro for Glob {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "glob",
+            description: "List the paths that match a pattern like `src/*.rs`.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let pattern = argument.as_string()?;
+        let paths = glob::glob(pattern).map_err(|error| Error::MacroFailure(error.to_string()))?;
+        let mut matches = Vec::new();
+
+        for path in paths {
+            let path = path.map_err(|error| Error::MacroFailure(error.to_string()))?;
+
+            matches.push(Value::String(path.to_string_lossy().to_string()));
+        }
+
+        Ok(Value::List(matches))
+    }
+}
+
+pub struct Grep;
+
+impl Macro for Grep {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "grep",
+            description: "Return the lines of a file that match a regular expression.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let lines = matching_lines(argument)?
+            .into_iter()
+            .map(Value::String)
+            .collect();
+
+        Ok(Value::List(lines))
+    }
+}
+
+pub struct GrepCount;
+
+impl Macro for GrepCount {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "grep_count",
+            description: "Count the lines of a file that match a regular expression.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let count = matching_lines(argument)?.len();
+
+        Ok(Value::Integer(count as i64))
+    }
+}
+
+/// Reads the lines matching the pattern from a `[pattern, path]` argument.
+fn matching_lines(argument: &Value) -> Result<Vec<String>> {
+    let argument = argument.as_fixed_len_list(2)?;
+    let regex = compile_regex(argument[0].as_string()?)?;
+    let path = argument[1].as_string()?;
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if regex.is_match(&line) {
+            lines.push(line);
+        }
+    }
+
+    Ok(lines)
+}
+
+fn compile_regex(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern)
+        .map_err(|error| Error::invalid_regex(pattern.to_string(), error.to_string()))
+}
+
 pub struct MoveDir;
 
 impl Macro for MoveDir {
@@ -243,6 +631,78 @@ impl Macro for MoveDir {
     }
 }
 
+pub struct Sed;
+
+impl Macro for Sed {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "sed",
+            description: "Replace regular expression matches on each line of a file and return the new content. Add `true` as a fourth argument to write the file in place.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_list()?;
+        let in_place = match argument.len() {
+            3 => false,
+            4 => argument[3].as_boolean()?,
+            _ => {
+                return Err(Error::ExpectedFunctionArgumentAmount {
+                    identifier: self.info().identifier.to_string(),
+                    expected: 3,
+                    actual: argument.len(),
+                })
+            }
+        };
+        let regex = compile_regex(argument[0].as_string()?)?;
+        let replacement = argument[1].as_string()?;
+        let path = argument[2].as_string()?;
+        let file_contents = fs::read_to_string(path)?;
+        let mut output = String::with_capacity(file_contents.len());
+
+        for line in file_contents.split_inclusive('\n') {
+            let (line, newline) = match line.strip_suffix('\n') {
+                Some(line) => (line, "\n"),
+                None => (line, ""),
+            };
+
+            output.push_str(&regex.replace_all(line, replacement.as_str()));
+            output.push_str(newline);
+        }
+
+        if in_place {
+            fs::write(path, output)?;
+
+            Ok(Value::Empty)
+        } else {
+            Ok(Value::String(output))
+        }
+    }
+}
+
+pub struct Tee;
+
+impl Macro for Tee {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "tee",
+            description: "Write a value to a file as text and return the value unchanged.",
+            group: "filesystem",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let value = &argument[0];
+        let path = argument[1].as_string()?;
+
+        fs::write(path, value.to_string())?;
+
+        Ok(value.clone())
+    }
+}
+
 pub struct Trash;
 
 impl Macro for Trash {
@@ -330,26 +790,52 @@ impl Macro for Watch {
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
-        let argument = argument.as_string()?;
-        let path = PathBuf::from(argument);
-        let modified_old = path.metadata()?.modified()?;
-        let wait_time = loop {
-            let modified_new = path.metadata()?.modified()?;
-
-            if modified_old != modified_new {
-                break modified_new
-                    .duration_since(modified_old)
-                    .unwrap_or_default()
-                    .as_millis() as i64;
+        watch_path(argument.as_string()?, &INTERRUPTED)
+    }
+}
+
+/// Waits for a change to the path, checking the interrupt flag while no events arrive.
+fn watch_path(argument: &str, interrupted: &AtomicBool) -> Result<Value> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    let path = PathBuf::from(argument);
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)
+        .map_err(|error| Error::MacroFailure(error.to_string()))?;
+
+    watcher
+        .watch(&path, RecursiveMode::NonRecursive)
+        .map_err(|error| Error::MacroFailure(error.to_string()))?;
+
+    loop {
+        let event = match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(event) => event.map_err(|error| Error::MacroFailure(error.to_string()))?,
+            Err(RecvTimeoutError::Timeout) => {
+                check_interrupt(interrupted)?;
+
+                continue;
             }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let kind = match event.kind {
+            EventKind::Create(_) => "created",
+            EventKind::Modify(_) => "modified",
+            EventKind::Remove(_) => "removed",
+            EventKind::Any | EventKind::Access(_) | EventKind::Other => continue,
         };
 
-        Ok(Value::Integer(wait_time))
+        return Ok(Value::String(kind.to_string()));
     }
+
+    Err(Error::MacroFailure(format!(
+        "Stopped watching {argument} before it changed."
+    )))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::Function;
+
     use super::*;
 
     #[test]
@@ -440,4 +926,360 @@ mod tests {
 
         assert!(!path.exists());
     }
+
+    #[test]
+    fn save_and_load_context() {
+        let path = Value::String("./target/save_context.json".to_string());
+        let mut context = VariableMap::new();
+
+        context.set_value("x", Value::Integer(1)).unwrap();
+        context
+            .set_value("y", Value::String("two".to_string()))
+            .unwrap();
+        context
+            .set_value("z.list", Value::List(vec![Value::Float(3.0)]))
+            .unwrap();
+
+        SaveContext.run_with_context(&path, &mut context).unwrap();
+
+        let mut loaded = VariableMap::new();
+
+        LoadContext.run_with_context(&path, &mut loaded).unwrap();
+
+        assert_eq!(context, loaded);
+    }
+
+    #[test]
+    fn save_and_load_constants() {
+        let path = Value::String("./target/save_constants.json".to_string());
+        let mut context = VariableMap::new();
+
+        context.set_constant("x", Value::Integer(1)).unwrap();
+        context.set_value("y", Value::Integer(2)).unwrap();
+        SaveContext.run_with_context(&path, &mut context).unwrap();
+
+        let mut loaded = VariableMap::new();
+
+        LoadContext.run_with_context(&path, &mut loaded).unwrap();
+
+        assert_eq!(vec!["x"], loaded.constants().collect::<Vec<_>>());
+        assert!(loaded.set_value("x", Value::Integer(3)).is_err());
+    }
+
+    #[test]
+    fn load_context_over_a_constant_changes_nothing() {
+        let path = Value::String("./target/load_over_constant.json".to_string());
+        let mut saved = VariableMap::new();
+
+        saved.set_value("a", Value::Integer(1)).unwrap();
+        saved.set_value("b", Value::Integer(2)).unwrap();
+        SaveContext.run_with_context(&path, &mut saved).unwrap();
+
+        let mut context = VariableMap::new();
+
+        context.set_constant("b", Value::Integer(0)).unwrap();
+
+        assert!(LoadContext.run_with_context(&path, &mut context).is_err());
+        assert_eq!(None, context.get_value("a").unwrap());
+        assert_eq!(Some(Value::Integer(0)), context.get_value("b").unwrap());
+    }
+
+    #[test]
+    fn load_missing_context() {
+        let path = Value::String("./target/missing_context.json".to_string());
+        let _ = std::fs::remove_file("./target/missing_context.json");
+
+        assert!(LoadContext
+            .run_with_context(&path, &mut VariableMap::new())
+            .is_err());
+    }
+
+    #[test]
+    fn each_line() {
+        let path = "./target/each_line.txt";
+
+        fs::write(path, "one\ntwo\nthree\n").unwrap();
+
+        let result = EachLine.run(&Value::List(vec![
+            Value::String(path.to_string()),
+            Value::Function(Function::new("count input")),
+        ]));
+
+        assert_eq!(
+            Ok(Value::List(vec![
+                Value::Integer(3),
+                Value::Integer(3),
+                Value::Integer(5),
+            ])),
+            result
+        );
+    }
+
+    #[test]
+    fn grep() {
+        let path = Value::String("./target/grep.txt".to_string());
+
+        fs::write("./target/grep.txt", "apple\nbanana\napricot\n").unwrap();
+
+        let arguments = Value::List(vec![Value::String("^ap".to_string()), path.clone()]);
+
+        assert_eq!(
+            Ok(Value::List(vec![
+                Value::String("apple".to_string()),
+                Value::String("apricot".to_string()),
+            ])),
+            Grep.run(&arguments)
+        );
+        assert_eq!(Ok(Value::Integer(2)), GrepCount.run(&arguments));
+
+        let invalid = Value::List(vec![Value::String("(".to_string()), path]);
+
+        assert!(matches!(
+            Grep.run(&invalid),
+            Err(Error::InvalidRegex { .. })
+        ));
+    }
+
+    #[test]
+    fn sed() {
+        let path = "./target/sed.txt";
+
+        fs::write(path, "one fish\ntwo fish\nred bird\n").unwrap();
+
+        let result = Sed.run(&Value::List(vec![
+            Value::String("fish".to_string()),
+            Value::String("cat".to_string()),
+            Value::String(path.to_string()),
+        ]));
+
+        assert_eq!(
+            Ok(Value::String("one cat\ntwo cat\nred bird\n".to_string())),
+            result
+        );
+
+        Sed.run(&Value::List(vec![
+            Value::String("^(\\w+)".to_string()),
+            Value::String("[$1]".to_string()),
+            Value::String(path.to_string()),
+            Value::Boolean(true),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            "[one] fish\n[two] fish\n[red] bird\n",
+            fs::read_to_string(path).unwrap()
+        );
+    }
+
+    #[test]
+    fn tee() {
+        let path = "./target/tee.txt";
+        let value = Value::String("hiya".to_string());
+        let _ = std::fs::remove_file(path);
+
+        let result = Tee.run(&Value::List(vec![
+            value.clone(),
+            Value::String(path.to_string()),
+        ]));
+
+        assert_eq!(Ok(value), result);
+        assert_eq!("hiya", fs::read_to_string(path).unwrap());
+    }
+
+    #[test]
+    fn cat() {
+        fs::write("./target/cat_1.txt", "foo\n").unwrap();
+        fs::write("./target/cat_2.txt", "bar\n").unwrap();
+
+        let result = Cat.run(&Value::List(vec![
+            Value::String("./target/cat_1.txt".to_string()),
+            Value::String("./target/cat_2.txt".to_string()),
+        ]));
+
+        assert_eq!(Ok(Value::String("foo\nbar\n".to_string())), result);
+    }
+
+    #[test]
+    fn cat_missing_file() {
+        let _ = std::fs::remove_file("./target/cat_missing.txt");
+
+        fs::write("./target/cat_1.txt", "foo\n").unwrap();
+
+        let result = Cat.run(&Value::List(vec![
+            Value::String("./target/cat_1.txt".to_string()),
+            Value::String("./target/cat_missing.txt".to_string()),
+        ]));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn watch() {
+        let path = "./target/watch.txt";
+
+        fs::write(path, "").unwrap();
+
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            fs::write(path, "hiya").unwrap();
+        });
+        let result = Watch.run(&Value::String(path.to_string()));
+
+        writer.join().unwrap();
+
+        assert_eq!(Ok(Value::String("modified".to_string())), result);
+    }
+
+    #[test]
+    fn watch_is_interrupted() {
+        let path = "./target/watch_interrupted.txt";
+        let interrupted = AtomicBool::new(true);
+
+        fs::write(path, "").unwrap();
+
+        assert_eq!(Err(Error::Interrupted), watch_path(path, &interrupted));
+    }
+
+    #[test]
+    fn glob() {
+        let dir = "./target/glob";
+        let _ = fs::remove_dir_all(dir);
+
+        fs::create_dir_all(dir).unwrap();
+        fs::write("./target/glob/a.txt", "").unwrap();
+        fs::write("./target/glob/b.txt", "").unwrap();
+        fs::write("./target/glob/c.rs", "").unwrap();
+
+        let result = Glob.run(&Value::String("./target/glob/*.txt".to_string()));
+
+        assert_eq!(
+            Ok(Value::List(vec![
+                Value::String("target/glob/a.txt".to_string()),
+                Value::String("target/glob/b.txt".to_string()),
+            ])),
+            result
+        );
+        assert!(matches!(
+            Glob.run(&Value::String("***".to_string())),
+            Err(Error::MacroFailure(_))
+        ));
+    }
+
+    #[test]
+    fn remove_empty_dir() {
+        let path = PathBuf::from("./target/remove_empty_dir");
+        let _ = fs::create_dir_all(&path);
+
+        RemoveDir
+            .run(&Value::String(path.to_string_lossy().to_string()))
+            .unwrap();
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn remove_populated_dir() {
+        let path = PathBuf::from("./target/remove_populated_dir");
+        let _ = fs::create_dir_all(path.join("nested"));
+
+        fs::write(path.join("file.txt"), "hiya").unwrap();
+        fs::write(path.join("nested/file.txt"), "hiya").unwrap();
+
+        RemoveDir
+            .run(&Value::String(path.to_string_lossy().to_string()))
+            .unwrap();
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn remove_missing_dir() {
+        let result = RemoveDir.run(&Value::String("./target/remove_missing_dir".to_string()));
+
+        assert!(matches!(result, Err(Error::MacroFailure(_))));
+    }
+
+    #[test]
+    fn file_exists() {
+        let path = "./target/file_exists.txt";
+
+        fs::write(path, "hiya").unwrap();
+
+        assert_eq!(
+            FileExists.run(&Value::String(path.to_string())).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            FileExists
+                .run(&Value::String("./target/no_such_file.txt".to_string()))
+                .unwrap(),
+            Value::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn is_dir() {
+        let path = "./target/is_dir.txt";
+
+        fs::write(path, "hiya").unwrap();
+
+        assert_eq!(
+            IsDir.run(&Value::String("./target".to_string())).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            IsDir.run(&Value::String(path.to_string())).unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            IsDir
+                .run(&Value::String("./target/no_such_dir".to_string()))
+                .unwrap(),
+            Value::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn hash_file() {
+        let path = "./target/hash_file.txt";
+
+        fs::write(path, "hiya").unwrap();
+
+        assert_eq!(
+            HashFile.run(&Value::String(path.to_string())).unwrap(),
+            Value::String(
+                "10e5ede4715c80885bf4ca6f9ea87d841cbd24cd0b6bd1af5f1edc9b561052de".to_string()
+            )
+        );
+        assert_eq!(
+            HashFile
+                .run(&Value::List(vec![
+                    Value::String(path.to_string()),
+                    Value::String("md5".to_string())
+                ]))
+                .unwrap(),
+            Value::String("e2c50ded5d3990bdabeb4b44c4411f18".to_string())
+        );
+        assert!(matches!(
+            HashFile.run(&Value::List(vec![
+                Value::String(path.to_string()),
+                Value::String("crc32".to_string())
+            ])),
+            Err(Error::CustomMessage(_))
+        ));
+    }
+
+    #[test]
+    fn read_bytes() {
+        let path = "./target/read_bytes.bin";
+        let bytes = [0xff, 0xfe, 0x00, 0x80, b'w'];
+
+        fs::write(path, bytes).unwrap();
+
+        let encoded = ReadBytes.run(&Value::String(path.to_string())).unwrap();
+        let decoded = STANDARD.decode(encoded.as_string().unwrap()).unwrap();
+
+        assert_eq!(fs::metadata(path).unwrap().len(), decoded.len() as u64);
+        assert_eq!(bytes.to_vec(), decoded);
+    }
 }
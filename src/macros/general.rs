@@ -1,8 +1,179 @@
-use std::{fs, thread::sleep, time::Duration};
+use std::{
+    fs,
+    io::{self, Write},
+    thread::sleep,
+    time::{Duration, Instant},
+};
 
-use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
-use crate::{Function, Macro, MacroInfo, Result, Value};
+use crate::{
+    clear_interrupt, eval,
+    interrupt::{sleep_interruptible, INTERRUPTED},
+    is_interrupted, Error, Function, Macro, MacroInfo, Result, Table, Value, VariableMap,
+};
+
+pub struct Beep;
+
+impl Macro for Beep {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "beep",
+            description: "Ring the terminal bell.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        argument.as_empty()?;
+
+        ring_bell(&mut io::stderr())?;
+
+        Ok(Value::Empty)
+    }
+}
+
+fn ring_bell(output: &mut impl Write) -> Result<()> {
+    output.write_all(b"\x07")?;
+    output.flush()?;
+
+    Ok(())
+}
+
+pub struct Context;
+
+impl Macro for Context {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "context",
+            description: "Return every variable that is currently defined as a map.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, _argument: &Value) -> Result<Value> {
+        Err(Error::ContextNotMutable)
+    }
+
+    fn run_with_read_context(&self, _argument: &Value, context: &VariableMap) -> Result<Value> {
+        Ok(Value::Map(context.clone()))
+    }
+}
+
+pub struct Default;
+
+impl Macro for Default {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "default",
+            description: "Return the fallback if the value is empty, otherwise return the value.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let (value, fallback) = (&argument[0], &argument[1]);
+
+        if value.is_empty() {
+            Ok(fallback.clone())
+        } else {
+            Ok(value.clone())
+        }
+    }
+}
+
+pub struct Eprint;
+
+impl Macro for Eprint {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "eprint",
+            description: "Print a value to stderr, keeping it out of piped output.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        eprint_value(argument, &mut io::stderr())?;
+
+        Ok(Value::Empty)
+    }
+}
+
+fn eprint_value(value: &Value, output: &mut impl Write) -> Result<()> {
+    writeln!(output, "{value}")?;
+    output.flush()?;
+
+    Ok(())
+}
+
+pub struct Eval;
+
+impl Macro for Eval {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "eval",
+            description: "Evaluate a string of whale source with its own variables.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let source = argument.as_string()?;
+
+        eval(source)
+    }
+}
+
+pub struct Notify;
+
+impl Macro for Notify {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "notify",
+            description: "Send a desktop notification with a title and body.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let title = argument[0].as_string()?;
+        let body = argument[1].as_string()?;
+
+        notify_or_print(
+            title,
+            body,
+            |title, body| {
+                notify_rust::Notification::new()
+                    .summary(title)
+                    .body(body)
+                    .show()
+                    .map(|_| ())
+                    .map_err(|error| error.to_string())
+            },
+            &mut io::stderr(),
+        )?;
+
+        Ok(Value::Empty)
+    }
+}
+
+/// Try to show a notification and print it to `fallback` if that fails, so that
+/// scripts still work on systems without a notification service.
+fn notify_or_print(
+    title: &str,
+    body: &str,
+    show: impl FnOnce(&str, &str) -> std::result::Result<(), String>,
+    fallback: &mut impl Write,
+) -> Result<()> {
+    if show(title, body).is_err() {
+        writeln!(fallback, "{title}: {body}")?;
+    }
+
+    Ok(())
+}
 
 pub struct Output;
 
@@ -21,6 +192,69 @@ impl Macro for Output {
         Ok(Value::Empty)
     }
 }
+
+pub struct Pipe;
+
+impl Macro for Pipe {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "pipe",
+            description:
+                "Pass a value through a list of functions, each receiving the last output as input.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_list()?;
+
+        Error::expected_minimum_function_argument_amount(
+            self.info().identifier,
+            argument.len(),
+            1,
+        )?;
+
+        let mut accumulator = argument[0].clone();
+
+        for value in &argument[1..] {
+            let function = value.as_function()?;
+            let mut context = VariableMap::new();
+
+            context.set_value("input", accumulator)?;
+
+            accumulator = function.run_with_context(&mut context)?;
+        }
+
+        Ok(accumulator)
+    }
+}
+
+pub struct Print;
+
+impl Macro for Print {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "print",
+            description: "Print a value without a trailing newline.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        print_value(argument, &mut io::stdout())?;
+
+        Ok(Value::Empty)
+    }
+}
+
+/// Writes the value and flushes right away, since there is no newline to trigger a flush.
+fn print_value(value: &Value, output: &mut impl Write) -> Result<()> {
+    write!(output, "{value}")?;
+    output.flush()?;
+
+    Ok(())
+}
+
 pub struct Repeat;
 
 impl Macro for Repeat {
@@ -73,13 +307,22 @@ impl Macro for Async {
     fn info(&self) -> MacroInfo<'static> {
         MacroInfo {
             identifier: "async",
-            description: "Run functions in parallel.",
+            description: "Run functions in parallel. Add `true` after the list for a table of results and errors.",
             group: "general",
         }
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
         let argument_list = argument.as_list()?;
+
+        if let [Value::List(functions), Value::Boolean(detailed)] = argument_list.as_slice() {
+            if *detailed {
+                return run_async_detailed(functions);
+            }
+
+            return self.run(&argument_list[0]);
+        }
+
         let results = argument_list
             .par_iter()
             .map(|value| {
@@ -100,6 +343,94 @@ impl Macro for Async {
     }
 }
 
+/// Run functions in parallel and collect a table with each task's index, result and error.
+fn run_async_detailed(functions: &[Value]) -> Result<Value> {
+    let rows: Vec<Vec<Value>> = functions
+        .par_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let index = Value::Integer(index as i64);
+            let function = if let Ok(function) = value.as_function() {
+                function
+            } else {
+                return vec![index, value.clone(), Value::Empty];
+            };
+
+            match function.run() {
+                Ok(value) => vec![index, value, Value::Empty],
+                Err(error) => vec![index, Value::Empty, Value::String(error.to_string())],
+            }
+        })
+        .collect();
+    let mut table = Table::new(vec![
+        "index".to_string(),
+        "result".to_string(),
+        "error".to_string(),
+    ]);
+
+    table.reserve(rows.len());
+
+    for row in rows {
+        table.insert(row)?;
+    }
+
+    Ok(Value::Table(table))
+}
+
+pub struct Unset;
+
+impl Macro for Unset {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "unset",
+            description: "Remove a variable by name and return whether it existed.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, _argument: &Value) -> Result<Value> {
+        Err(Error::ContextNotMutable)
+    }
+
+    fn run_with_context(&self, argument: &Value, context: &mut VariableMap) -> Result<Value> {
+        let identifier = argument.as_string()?;
+        let removed = context.remove_value(identifier)?;
+
+        Ok(Value::Boolean(removed.is_some()))
+    }
+}
+
+pub struct Vars;
+
+impl Macro for Vars {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "vars",
+            description: "List the names of the variables under a dotted prefix.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, _argument: &Value) -> Result<Value> {
+        Err(Error::ContextNotMutable)
+    }
+
+    fn run_with_read_context(&self, argument: &Value, context: &VariableMap) -> Result<Value> {
+        let prefix = if argument.is_empty() {
+            ""
+        } else {
+            argument.as_string()?
+        };
+        let keys = context
+            .keys_with_prefix(prefix)?
+            .into_iter()
+            .map(Value::String)
+            .collect();
+
+        Ok(Value::List(keys))
+    }
+}
+
 pub struct Wait;
 
 impl Macro for Wait {
@@ -114,8 +445,315 @@ impl Macro for Wait {
     fn run(&self, argument: &Value) -> Result<Value> {
         let argument = argument.as_int()?;
 
-        sleep(Duration::from_millis(argument as u64));
+        sleep_interruptible(Duration::from_millis(argument as u64), &INTERRUPTED)?;
+
+        Ok(Value::Empty)
+    }
+}
+
+pub struct WatchCommand;
+
+impl Macro for WatchCommand {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "watch_command",
+            description: "Clear the screen and run a function every given number of milliseconds until Ctrl-C is pressed.",
+            group: "general",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let milliseconds = argument[0].as_int()?;
+
+        if milliseconds < 0 {
+            return Err(Error::MacroFailure(format!(
+                "The interval must not be negative, got {milliseconds}."
+            )));
+        }
+
+        let interval = Duration::from_millis(milliseconds as u64);
+        let function = argument[1].as_function()?;
+
+        clear_interrupt();
+
+        run_repeatedly(interval, is_interrupted, || {
+            print!("\x1B[2J\x1B[1;1H");
+
+            match function.run() {
+                Ok(value) => println!("{value}"),
+                Err(error) => eprintln!("{error}"),
+            }
+
+            Ok(())
+        })?;
+
+        // Ctrl-C only stops the watch, not whatever is evaluated after it.
+        clear_interrupt();
 
         Ok(Value::Empty)
     }
 }
+
+/// Call `run` once per interval until `is_interrupted` returns true. The interrupt is
+/// checked while waiting so that long intervals can still be stopped promptly. Returns
+/// the number of times `run` was called.
+fn run_repeatedly(
+    interval: Duration,
+    is_interrupted: impl Fn() -> bool,
+    mut run: impl FnMut() -> Result<()>,
+) -> Result<usize> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    let mut iterations = 0;
+
+    while !is_interrupted() {
+        run()?;
+
+        iterations += 1;
+
+        let started = Instant::now();
+
+        while let Some(remaining) = interval.checked_sub(started.elapsed()) {
+            if is_interrupted() {
+                return Ok(iterations);
+            }
+
+            sleep(remaining.min(POLL_INTERVAL));
+        }
+    }
+
+    Ok(iterations)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval_with_context;
+
+    use super::*;
+
+    #[test]
+    fn beep() {
+        let mut output = Vec::new();
+
+        ring_bell(&mut output).unwrap();
+
+        assert_eq!(b"\x07", output.as_slice());
+        assert_eq!(Ok(Value::Empty), Beep.run(&Value::Empty));
+    }
+
+    #[test]
+    fn print_without_newline() {
+        let mut output = Vec::new();
+
+        print_value(&Value::String("one".to_string()), &mut output).unwrap();
+        print_value(&Value::Integer(2), &mut output).unwrap();
+
+        assert_eq!(b"one2", output.as_slice());
+    }
+
+    #[test]
+    fn eprint_returns_empty() {
+        assert_eq!(
+            Ok(Value::Empty),
+            Eprint.run(&Value::String("diagnostic".to_string()))
+        );
+    }
+
+    #[test]
+    fn eprint_writes_line() {
+        let mut output = Vec::new();
+
+        eprint_value(&Value::String("diagnostic".to_string()), &mut output).unwrap();
+
+        assert_eq!(b"diagnostic\n".to_vec(), output);
+    }
+
+    #[test]
+    fn eval_string() {
+        let result = Eval.run(&Value::String("1 + 2".to_string()));
+
+        assert_eq!(Ok(Value::Integer(3)), result);
+    }
+
+    #[test]
+    fn eval_does_not_share_variables() {
+        Eval.run(&Value::String("x = 1".to_string())).unwrap();
+
+        let result = Eval.run(&Value::String("x".to_string()));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn async_detailed() {
+        let functions = Value::List(vec![
+            Value::Function(Function::new("1 + 1")),
+            Value::Function(Function::new("undefined_variable")),
+        ]);
+        let result = Async
+            .run(&Value::List(vec![functions, Value::Boolean(true)]))
+            .unwrap();
+        let table = result.as_table().unwrap();
+
+        assert_eq!(
+            &vec![Value::Integer(0), Value::Integer(2), Value::Empty],
+            table.get(0).unwrap()
+        );
+
+        let failed = table.get(1).unwrap();
+
+        assert_eq!(Value::Integer(1), failed[0]);
+        assert_eq!(Value::Empty, failed[1]);
+        assert!(failed[2].is_string());
+    }
+
+    #[test]
+    fn pipe() {
+        let arguments = Value::List(vec![
+            Value::Integer(5),
+            Value::Function(Function::new("input + 1")),
+            Value::Function(Function::new("input * 2")),
+        ]);
+
+        assert_eq!(Ok(Value::Integer(12)), Pipe.run(&arguments));
+    }
+
+    #[test]
+    fn default() {
+        let fallback = Value::String("n/a".to_string());
+
+        assert_eq!(
+            Ok(fallback.clone()),
+            Default.run(&Value::List(vec![Value::Empty, fallback.clone()]))
+        );
+        assert_eq!(
+            Ok(Value::Integer(1)),
+            Default.run(&Value::List(vec![Value::Integer(1), fallback]))
+        );
+    }
+
+    #[test]
+    fn unset() {
+        let mut context = VariableMap::new();
+
+        context.set_value("x", Value::Integer(1)).unwrap();
+        context.set_value("map.key", Value::Integer(2)).unwrap();
+
+        let unset_variable = Unset.run_with_context(&Value::String("x".to_string()), &mut context);
+        let unset_key = Unset.run_with_context(&Value::String("map.key".to_string()), &mut context);
+        let unset_missing = Unset.run_with_context(&Value::String("x".to_string()), &mut context);
+
+        assert_eq!(Ok(Value::Boolean(true)), unset_variable);
+        assert_eq!(Ok(Value::Boolean(true)), unset_key);
+        assert_eq!(Ok(Value::Boolean(false)), unset_missing);
+        assert_eq!(None, context.get_value("x").unwrap());
+        assert_eq!(None, context.get_value("map.key").unwrap());
+    }
+
+    #[test]
+    fn unset_from_source() {
+        let mut context = VariableMap::new();
+
+        eval_with_context("x = 1; unset \"x\"", &mut context).unwrap();
+
+        assert!(eval_with_context("x", &mut context).is_err());
+    }
+
+    #[test]
+    fn vars() {
+        let mut context = VariableMap::new();
+
+        eval_with_context("config.a = 1; config.b = 2", &mut context).unwrap();
+
+        let result = eval_with_context("vars \"config\"", &mut context);
+
+        assert_eq!(
+            Ok(Value::List(vec![
+                Value::String("config.a".to_string()),
+                Value::String("config.b".to_string()),
+            ])),
+            result
+        );
+    }
+
+    #[test]
+    fn context() {
+        let mut context = VariableMap::new();
+
+        eval_with_context("x = 1; y = \"two\"", &mut context).unwrap();
+
+        let dump = Context
+            .run_with_context(&Value::Empty, &mut context)
+            .unwrap();
+        let dump = dump.as_map().unwrap();
+
+        assert_eq!(Some(Value::Integer(1)), dump.get_value("x").unwrap());
+        assert_eq!(
+            Some(Value::String("two".to_string())),
+            dump.get_value("y").unwrap()
+        );
+    }
+
+    #[test]
+    fn watch_command_negative_interval() {
+        let function = Function::new("1");
+        let result = WatchCommand.run(&Value::List(vec![
+            Value::Integer(-1),
+            Value::Function(function),
+        ]));
+
+        assert!(matches!(result, Err(Error::MacroFailure(_))));
+    }
+
+    #[test]
+    fn run_repeatedly_until_interrupted() {
+        let runs = std::cell::Cell::new(0);
+        let iterations = run_repeatedly(
+            Duration::from_millis(1),
+            || runs.get() == 3,
+            || {
+                runs.set(runs.get() + 1);
+
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(3, iterations);
+    }
+
+    #[test]
+    fn run_repeatedly_waits_for_interval() {
+        let started = Instant::now();
+        let runs = std::cell::Cell::new(0);
+
+        run_repeatedly(
+            Duration::from_millis(20),
+            || runs.get() == 2,
+            || {
+                runs.set(runs.get() + 1);
+
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn notify_without_notification_service() {
+        let mut output = Vec::new();
+
+        notify_or_print(
+            "whale",
+            "done",
+            |_, _| Err("unsupported".to_string()),
+            &mut output,
+        )
+        .unwrap();
+
+        assert_eq!(b"whale: done\n", output.as_slice());
+    }
+}
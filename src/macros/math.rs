@@ -0,0 +1,233 @@
+//! Macros for numbers and statistics.
+
+use crate::{Error, Macro, MacroInfo, Result, Table, Value};
+
+pub struct Bucketize;
+
+impl Macro for Bucketize {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "bucketize",
+            description: "Count the numbers in a list that fall between each pair of edges. Returns a table of \"lower-upper\" bucket labels and counts in the order of the edges.",
+            group: "math",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let numbers = argument[0]
+            .as_list()?
+            .iter()
+            .map(|value| value.as_number())
+            .collect::<Result<Vec<f64>>>()?;
+        let edges = argument[1].as_list()?;
+
+        if edges.len() < 2 {
+            return Err(Error::MacroFailure(
+                "bucketize needs at least two edges.".to_string(),
+            ));
+        }
+
+        let mut buckets = Table::new(vec!["bucket".to_string(), "count".to_string()]);
+
+        for (index, pair) in edges.windows(2).enumerate() {
+            let lower = pair[0].as_number()?;
+            let upper = pair[1].as_number()?;
+
+            if lower >= upper {
+                return Err(Error::MacroFailure(
+                    "The bucket edges must be in ascending order.".to_string(),
+                ));
+            }
+
+            // Each bucket includes its lower edge. The last one also includes its upper edge.
+            let is_last = index == edges.len() - 2;
+            let count = numbers
+                .iter()
+                .filter(|number| {
+                    **number >= lower && (**number < upper || is_last && **number == upper)
+                })
+                .count();
+
+            // A map would sort the labels as strings, so "10-20" would come before "5-10".
+            buckets.insert(vec![
+                Value::String(format!("{}-{}", pair[0], pair[1])),
+                Value::Integer(count as i64),
+            ])?;
+        }
+
+        Ok(Value::Table(buckets))
+    }
+}
+
+pub struct Percentile;
+
+impl Macro for Percentile {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "percentile",
+            description: "Get the p-th percentile (0 to 100) of a list of numbers, interpolating between the closest ranks.",
+            group: "math",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let numbers = sorted_numbers(&argument[0])?;
+        let percent = argument[1].as_number()?;
+
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(Error::MacroFailure(format!(
+                "The percentile must be between 0 and 100, not {percent}."
+            )));
+        }
+
+        Ok(Value::Float(percentile(&numbers, percent)))
+    }
+}
+
+pub struct Quartiles;
+
+impl Macro for Quartiles {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "quartiles",
+            description: "Get the first, second and third quartiles of a list of numbers.",
+            group: "math",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let numbers = sorted_numbers(argument)?;
+        let quartiles = [25.0, 50.0, 75.0]
+            .iter()
+            .map(|percent| Value::Float(percentile(&numbers, *percent)))
+            .collect();
+
+        Ok(Value::List(quartiles))
+    }
+}
+
+/// Converts a list of integers and floats to a sorted list of floats. Empty lists are an error
+/// because they have no percentiles.
+fn sorted_numbers(list: &Value) -> Result<Vec<f64>> {
+    let mut numbers = list
+        .as_list()?
+        .iter()
+        .map(|value| value.as_number())
+        .collect::<Result<Vec<f64>>>()?;
+
+    if numbers.is_empty() {
+        return Err(Error::MacroFailure(
+            "Cannot get a percentile of an empty list.".to_string(),
+        ));
+    }
+
+    numbers.sort_by(|left, right| left.total_cmp(right));
+
+    Ok(numbers)
+}
+
+/// Linear interpolation between the closest ranks of a sorted, non-empty list.
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = percent / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+
+    sorted[lower] + (rank - lower as f64) * (sorted[upper] - sorted[lower])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(values: &[i64]) -> Value {
+        Value::List(values.iter().copied().map(Value::Integer).collect())
+    }
+
+    #[test]
+    fn percentile_median() {
+        let odd = numbers(&[7, 1, 5, 3, 9]);
+        let even = numbers(&[4, 1, 3, 2]);
+
+        assert_eq!(
+            Ok(Value::Float(5.0)),
+            Percentile.run(&Value::List(vec![odd, Value::Integer(50)]))
+        );
+        assert_eq!(
+            Ok(Value::Float(2.5)),
+            Percentile.run(&Value::List(vec![even, Value::Integer(50)]))
+        );
+    }
+
+    #[test]
+    fn quartiles() {
+        let data = numbers(&[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        assert_eq!(
+            Ok(Value::List(vec![
+                Value::Float(3.0),
+                Value::Float(5.0),
+                Value::Float(7.0),
+            ])),
+            Quartiles.run(&data)
+        );
+        assert_eq!(
+            Ok(Value::Float(3.0)),
+            Percentile.run(&Value::List(vec![data.clone(), Value::Integer(25)]))
+        );
+        assert_eq!(
+            Ok(Value::Float(7.0)),
+            Percentile.run(&Value::List(vec![data, Value::Float(75.0)]))
+        );
+    }
+
+    #[test]
+    fn bucketize() {
+        let data = numbers(&[1, 5, 9, 10, 15, 20, 25, 30, 31]);
+        let edges = numbers(&[5, 10, 20, 30]);
+        let result = Bucketize.run(&Value::List(vec![data, edges])).unwrap();
+        let buckets: Vec<(String, i64)> = result
+            .as_table()
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| {
+                (
+                    row[0].as_string().unwrap().clone(),
+                    row[1].as_int().unwrap(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("5-10".to_string(), 2),
+                ("10-20".to_string(), 2),
+                ("20-30".to_string(), 3),
+            ],
+            buckets
+        );
+    }
+
+    #[test]
+    fn bucketize_float_edges() {
+        let data = Value::List(vec![Value::Float(0.25), Value::Float(0.75)]);
+        let edges = Value::List(vec![
+            Value::Float(0.0),
+            Value::Float(0.5),
+            Value::Float(1.5),
+        ]);
+        let result = Bucketize.run(&Value::List(vec![data, edges])).unwrap();
+
+        assert_eq!(
+            &vec![Value::String("0.5-1.5".to_string()), Value::Integer(1)],
+            &result.as_table().unwrap().rows()[1]
+        );
+    }
+
+    #[test]
+    fn percentile_of_empty_list() {
+        assert!(Quartiles.run(&Value::List(Vec::new())).is_err());
+    }
+}
@@ -19,19 +19,22 @@
 //!
 //! assert_eq!(count, 3);
 //! ```
-use crate::{Result, Value};
+use crate::{Result, Value, VariableMap};
 
 mod collections;
 mod command;
 mod data_formats;
 mod disks;
+mod encoding;
 mod filesystem;
 mod general;
 mod gui;
 mod logic;
+mod math;
 mod network;
 mod package_management;
 mod random;
+mod string;
 mod system;
 mod test;
 mod time;
@@ -40,46 +43,107 @@ mod time;
 ///
 /// This list is used to match identifiers with macros and to provide info to
 /// the shell.
-pub const MACRO_LIST: [&'static dyn Macro; 54] = [
+pub const MACRO_LIST: [&'static dyn Macro; 140] = [
+    &collections::AddColumn,
+    &collections::ApplyToColumn,
+    &collections::BottomN,
+    &collections::CoalesceColumns,
+    &collections::CoerceTypes,
     &collections::Count,
     &collections::CreateTable,
+    &collections::Crosstab,
+    &collections::Explode,
+    &collections::FillEmpty,
+    &collections::FillForward,
     &collections::Get,
+    &collections::Head,
     &collections::Insert,
+    &collections::Merge,
+    &collections::Pop,
+    &collections::Push,
+    &collections::Rank,
+    &collections::RenderTable,
     &collections::Rows,
+    &collections::Sample,
     &collections::Select,
     &collections::String,
+    &collections::Tabulate,
+    &collections::Tail,
+    &collections::ToMap,
+    &collections::TopN,
     &collections::Where,
     &command::Bash,
+    &command::DefaultShell,
+    &command::ExecPipeline,
     &command::Fish,
     &command::Raw,
+    &command::RunWithEnv,
     &command::Sh,
+    &command::Start,
     &command::Zsh,
     &data_formats::FromCsv,
-    &data_formats::ToCsv,
     &data_formats::FromJson,
+    &data_formats::ReadCsv,
+    &data_formats::ReadJson,
+    &data_formats::ToCsv,
     &data_formats::ToJson,
+    &data_formats::ToJsonPretty,
+    &data_formats::WriteCsv,
+    &data_formats::WriteJson,
     &disks::ListDisks,
     &disks::Partition,
+    &encoding::Base64Decode,
+    &encoding::Base64Encode,
+    &encoding::UrlDecode,
+    &encoding::UrlEncode,
     &filesystem::Append,
+    &filesystem::Cat,
     &filesystem::CreateDir,
+    &filesystem::EachLine,
+    &filesystem::FileExists,
     &filesystem::FileMetadata,
+    &filesystem::Glob,
+    &filesystem::Grep,
+    &filesystem::GrepCount,
+    &filesystem::HashFile,
+    &filesystem::IsDir,
+    &filesystem::LoadContext,
     &filesystem::MoveDir,
+    &filesystem::ReadBytes,
     &filesystem::ReadDir,
     &filesystem::ReadFile,
     &filesystem::RemoveDir,
+    &filesystem::SaveContext,
+    &filesystem::Sed,
+    &filesystem::Tee,
     &filesystem::Trash,
     &filesystem::Watch,
     &filesystem::Write,
     &general::Async,
+    &general::Beep,
+    &general::Context,
+    &general::Default,
+    &general::Eprint,
+    &general::Eval,
+    &general::Notify,
     &general::Output,
+    &general::Pipe,
+    &general::Print,
     &general::Repeat,
     &general::Run,
+    &general::Unset,
+    &general::Vars,
     &general::Wait,
+    &general::WatchCommand,
     &gui::BarGraph,
     &gui::Plot,
     &logic::If,
     &logic::Loop,
+    &math::Bucketize,
+    &math::Percentile,
+    &math::Quartiles,
     &network::Download,
+    &network::HttpGet,
     &package_management::CoprRepositories,
     &package_management::EnableRpmRepositories,
     &package_management::InstallPackage,
@@ -90,17 +154,60 @@ pub const MACRO_LIST: [&'static dyn Macro; 54] = [
     &random::RandomFloat,
     &random::RandomInteger,
     &random::RandomString,
+    &string::Bold,
+    &string::BoxText,
+    &string::Color,
+    &string::Cut,
+    &string::EndsWith,
+    &string::Format,
+    &string::PadLeft,
+    &string::PadRight,
+    &string::RepeatString,
+    &string::SortLines,
+    &string::StartsWith,
+    &string::StripAnsi,
+    &string::Underline,
+    &string::UniqLines,
+    &string::Wc,
+    &string::WcFile,
     &system::CpuSpeed,
+    &system::Memory,
+    &system::Open,
+    &system::SystemEnv,
+    &system::SystemInfo,
+    &system::TerminalSize,
     &test::Assert,
     &test::AssertEqual,
+    &time::Duration,
+    &time::FormatTime,
     &time::Local,
     &time::Now,
+    &time::NowUnix,
+    &time::ParseTime,
 ];
 
 /// A whale macro function.
 pub trait Macro: Sync + Send {
     fn info(&self) -> MacroInfo<'static>;
     fn run(&self, argument: &Value) -> Result<Value>;
+
+    /// Run the macro with read-only access to the caller's variables. Macros that do
+    /// not need the context only have to implement `run`.
+    fn run_with_read_context(&self, argument: &Value, _context: &VariableMap) -> Result<Value> {
+        self.run(argument)
+    }
+
+    /// Run the macro with access to the caller's variables. Only macros that change
+    /// the variables have to implement this.
+    fn run_with_context(&self, argument: &Value, context: &mut VariableMap) -> Result<Value> {
+        self.run_with_read_context(argument, context)
+    }
+
+    /// Returns true if running the macro with this argument hands the terminal's stdin to
+    /// another process, which may prompt the user. No spinner is drawn over those.
+    fn inherits_stdin(&self, _argument: &Value) -> bool {
+        false
+    }
 }
 
 /// Information needed for each macro.
@@ -115,27 +222,6 @@ pub struct MacroInfo<'a> {
     pub group: &'a str,
 }
 
-// pub struct SystemInfo;
-
-// impl Macro for SystemInfo {
-//     fn info(&self) -> MacroInfo<'static> {
-//         MacroInfo {
-//             identifier: "system_info",
-//             description: "Get information on the system.",
-//         }
-//     }
-
-//     fn run(&self, argument: &Value) -> crate::Result<Value> {
-//         argument.as_empty()?;
-
-//         let mut map = VariableMap::new();
-
-//         map.set_value("hostname", Value::String(hostname()?))?;
-
-//         Ok(Value::Map(map))
-//     }
-// }
-
 // pub struct Sort;
 
 // impl Macro for Sort {
@@ -1,6 +1,15 @@
 //! Macros for network access.
 
-use crate::{Macro, MacroInfo, Result, Value};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{Read, Write},
+};
+
+use crate::{
+    interrupt::{check_interrupt, INTERRUPTED},
+    Error, Macro, MacroInfo, Result, Value, VariableMap,
+};
 
 pub struct Download;
 
@@ -8,15 +17,197 @@ impl Macro for Download {
     fn info(&self) -> MacroInfo<'static> {
         MacroInfo {
             identifier: "download",
-            description: "Fetch a network resource.",
+            description: "Fetch a network resource. Pass a URL and a path to save it to a file.",
             group: "network",
         }
     }
 
     fn run(&self, argument: &Value) -> Result<Value> {
-        let argument = argument.as_string()?;
-        let output = reqwest::blocking::get(argument)?.text()?;
+        if let Ok(url) = argument.as_string() {
+            let mut response = reqwest::blocking::get(url)?;
+            let mut output = Vec::new();
+
+            copy_interruptible(&mut response, &mut output)?;
+
+            return Ok(Value::String(String::from_utf8_lossy(&output).to_string()));
+        }
+
+        let argument = argument.as_fixed_len_list(2)?;
+        let url = argument[0].as_string()?;
+        let path = argument[1].as_string()?;
+        let mut response = reqwest::blocking::get(url)?.error_for_status()?;
+        let mut file = File::create(path)?;
+
+        copy_interruptible(&mut response, &mut file)?;
+
+        Ok(Value::Empty)
+    }
+}
+
+/// Copy everything from `reader` to `writer` in chunks, stopping with
+/// [Error::Interrupted] if Ctrl-C is pressed between chunks.
+fn copy_interruptible(reader: &mut impl Read, writer: &mut impl Write) -> Result<()> {
+    let mut buffer = [0; 8192];
+
+    loop {
+        check_interrupt(&INTERRUPTED)?;
+
+        let read = reader.read(&mut buffer)?;
+
+        if read == 0 {
+            return Ok(());
+        }
+
+        writer.write_all(&buffer[..read])?;
+    }
+}
+
+pub struct HttpGet;
+
+impl Macro for HttpGet {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "http_get",
+            description: "Send a GET request and return the status, headers and body.",
+            group: "network",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let url = argument.as_string()?;
+        let response =
+            reqwest::blocking::get(url).map_err(|error| Error::MacroFailure(error.to_string()))?;
+        let status = response.status().as_u16() as i64;
+        let mut headers = BTreeMap::new();
+
+        // Repeated headers, like `set-cookie`, become a list with one item per header.
+        for name in response.headers().keys() {
+            let mut values = response
+                .headers()
+                .get_all(name)
+                .iter()
+                .map(|value| Value::String(String::from_utf8_lossy(value.as_bytes()).to_string()))
+                .collect::<Vec<Value>>();
+            let value = if values.len() == 1 {
+                values.remove(0)
+            } else {
+                Value::List(values)
+            };
+
+            headers.insert(name.as_str().to_string(), value);
+        }
+
+        let body = response
+            .text()
+            .map_err(|error| Error::MacroFailure(error.to_string()))?;
+        let mut map = VariableMap::new();
+
+        map.set_value("status", Value::Integer(status))?;
+        map.set_value("headers", Value::Map(VariableMap::from(headers)))?;
+        map.set_value("body", Value::String(body))?;
+
+        Ok(Value::Map(map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    /// Serve a single fixed response on a local port and return its URL.
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+
+            let _ = stream.read(&mut request).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        format!("http://{address}/")
+    }
+
+    #[test]
+    fn http_get() {
+        let url = serve_once(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: 4\r\nconnection: close\r\n\r\nhiya",
+        );
+        let result = HttpGet.run(&Value::String(url)).unwrap();
+        let map = result.as_map().unwrap();
+
+        assert_eq!(Some(Value::Integer(200)), map.get_value("status").unwrap());
+        assert_eq!(
+            Some(Value::String("text/plain".to_string())),
+            map.get_value("headers.content-type").unwrap()
+        );
+        assert_eq!(
+            Some(Value::String("hiya".to_string())),
+            map.get_value("body").unwrap()
+        );
+    }
+
+    #[test]
+    fn http_get_repeated_headers() {
+        let url = serve_once(
+            "HTTP/1.1 200 OK\r\nset-cookie: a=1\r\nset-cookie: b=2\r\nx.dotted: yes\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        );
+        let result = HttpGet.run(&Value::String(url)).unwrap();
+        let headers = result
+            .as_map()
+            .unwrap()
+            .get_value("headers")
+            .unwrap()
+            .unwrap();
+        let headers = headers.as_map().unwrap().inner();
+
+        assert_eq!(
+            Some(&Value::List(vec![
+                Value::String("a=1".to_string()),
+                Value::String("b=2".to_string()),
+            ])),
+            headers.get("set-cookie")
+        );
+        assert_eq!(
+            Some(&Value::String("yes".to_string())),
+            headers.get("x.dotted")
+        );
+    }
+
+    #[test]
+    fn http_get_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        drop(listener);
+
+        assert!(matches!(
+            HttpGet.run(&Value::String(url)),
+            Err(Error::MacroFailure(_))
+        ));
+    }
+
+    #[test]
+    fn download_to_file() {
+        let path = "./target/download.txt";
+        let url =
+            serve_once("HTTP/1.1 200 OK\r\ncontent-length: 4\r\nconnection: close\r\n\r\nhiya");
+        let _ = std::fs::remove_file(path);
+
+        let result = Download.run(&Value::List(vec![
+            Value::String(url),
+            Value::String(path.to_string()),
+        ]));
 
-        Ok(Value::String(output))
+        assert_eq!(Ok(Value::Empty), result);
+        assert_eq!("hiya", std::fs::read_to_string(path).unwrap());
     }
 }
@@ -1,5 +1,6 @@
 use std::process::Command;
 
+use super::command::default_shell;
 use crate::{Error, Macro, MacroInfo, Result, Value};
 
 pub struct CoprRepositories;
@@ -24,7 +25,7 @@ impl Macro for CoprRepositories {
             });
         };
 
-        Command::new("fish")
+        Command::new(default_shell())
             .arg("-c")
             .arg(format!("sudo dnf -y copr enable {repo_list_string}"))
             .spawn()?
@@ -32,6 +33,10 @@ impl Macro for CoprRepositories {
 
         Ok(Value::Empty)
     }
+
+    fn inherits_stdin(&self, _argument: &Value) -> bool {
+        true
+    }
 }
 
 pub struct InstallPackage;
@@ -59,7 +64,7 @@ impl Macro for InstallPackage {
             });
         };
 
-        Command::new("fish")
+        Command::new(default_shell())
             .arg("-c")
             .arg(format!("sudo dnf -y install {package_list_string}"))
             .spawn()?
@@ -67,6 +72,10 @@ impl Macro for InstallPackage {
 
         Ok(Value::Empty)
     }
+
+    fn inherits_stdin(&self, _argument: &Value) -> bool {
+        true
+    }
 }
 
 pub struct EnableRpmRepositories;
@@ -82,14 +91,14 @@ impl Macro for EnableRpmRepositories {
 
     fn run(&self, argument: &Value) -> Result<Value> {
         if let Ok(repo) = argument.as_string() {
-            Command::new("fish")
+            Command::new(default_shell())
                 .arg("-c")
                 .arg(format!("sudo dnf -y config-manager --add-repo {repo}"))
                 .spawn()?
                 .wait()?;
         } else if let Ok(repos) = argument.as_list() {
             for repo in repos {
-                Command::new("fish")
+                Command::new(default_shell())
                     .arg("-c")
                     .arg(format!("sudo dnf -y config-manager --add-repo {repo}"))
                     .spawn()?
@@ -103,6 +112,10 @@ impl Macro for EnableRpmRepositories {
 
         Ok(Value::Empty)
     }
+
+    fn inherits_stdin(&self, _argument: &Value) -> bool {
+        true
+    }
 }
 
 pub struct UninstallPackage;
@@ -130,7 +143,7 @@ impl Macro for UninstallPackage {
             });
         };
 
-        Command::new("fish")
+        Command::new(default_shell())
             .arg("-c")
             .arg(format!("sudo dnf -y remove {package_list_string}"))
             .spawn()?
@@ -138,6 +151,10 @@ impl Macro for UninstallPackage {
 
         Ok(Value::Empty)
     }
+
+    fn inherits_stdin(&self, _argument: &Value) -> bool {
+        true
+    }
 }
 
 pub struct UpgradePackages;
@@ -154,7 +171,7 @@ impl Macro for UpgradePackages {
     fn run(&self, argument: &Value) -> Result<Value> {
         argument.as_empty()?;
 
-        Command::new("fish")
+        Command::new(default_shell())
             .arg("-c")
             .arg("sudo dnf -y upgrade")
             .spawn()?
@@ -162,4 +179,8 @@ impl Macro for UpgradePackages {
 
         Ok(Value::Empty)
     }
+
+    fn inherits_stdin(&self, _argument: &Value) -> bool {
+        true
+    }
 }
@@ -0,0 +1,861 @@
+//! Macros for working with text.
+
+use std::{env, fs};
+
+use nu_ansi_term::{Color as AnsiColor, Style};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{Error, Macro, MacroInfo, Result, Table, Value, VariableMap};
+
+/// The longest string, in bytes, that macros building text from a length or count will
+/// create.
+const MAX_STRING_LENGTH: usize = 1 << 30;
+
+pub struct Bold;
+
+impl Macro for Bold {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "bold",
+            description: "Make a string bold in the terminal.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let text = argument.as_string()?;
+
+        Ok(Value::String(paint(text, Style::new().bold())))
+    }
+}
+
+pub struct BoxText;
+
+impl Macro for BoxText {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "box",
+            description: "Draw a border around text.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let text = argument.as_string()?;
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines
+            .iter()
+            .map(|line| display_width(line))
+            .max()
+            .unwrap_or(0);
+        let border = "─".repeat(width + 2);
+        let mut framed = format!("┌{border}┐\n");
+
+        for line in lines {
+            let padding = " ".repeat(width - display_width(line));
+
+            framed.push_str(&format!("│ {line}{padding} │\n"));
+        }
+
+        framed.push_str(&format!("└{border}┘"));
+
+        Ok(Value::String(framed))
+    }
+}
+
+/// The number of terminal columns a line takes up, ignoring ANSI escape codes.
+fn display_width(line: &str) -> usize {
+    UnicodeWidthStr::width(strip_ansi_escapes::strip_str(line).as_str())
+}
+
+pub struct Color;
+
+impl Macro for Color {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "color",
+            description: "Color a string in the terminal, e.g. [\"hiya\", \"red\"].",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let text = argument[0].as_string()?;
+        let color_name = argument[1].as_string()?;
+        let color = match color_name.to_lowercase().as_str() {
+            "black" => AnsiColor::Black,
+            "red" => AnsiColor::Red,
+            "green" => AnsiColor::Green,
+            "yellow" => AnsiColor::Yellow,
+            "blue" => AnsiColor::Blue,
+            "purple" | "magenta" => AnsiColor::Purple,
+            "cyan" => AnsiColor::Cyan,
+            "white" => AnsiColor::White,
+            "gray" | "grey" => AnsiColor::DarkGray,
+            _ => {
+                return Err(Error::CustomMessage(format!(
+                    "Unknown color {color_name}. Use black, red, green, yellow, blue, purple, cyan, white or gray."
+                )))
+            }
+        };
+
+        Ok(Value::String(paint(text, color.normal())))
+    }
+}
+
+pub struct Cut;
+
+impl Macro for Cut {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "cut",
+            description:
+                "Keep the given fields of each line, counting from zero, split by a delimiter.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(3)?;
+        let text = argument[0].as_string()?;
+        let delimiter = argument[1].as_string()?;
+        let field_indexes = if let Ok(index) = argument[2].as_int() {
+            vec![index]
+        } else {
+            argument[2]
+                .as_list()?
+                .iter()
+                .map(|index| index.as_int())
+                .collect::<Result<Vec<i64>>>()?
+        };
+
+        if let Some(index) = field_indexes.iter().find(|index| **index < 0) {
+            return Err(Error::MacroFailure(format!(
+                "Cannot cut field {index}. Fields are counted from zero."
+            )));
+        }
+
+        let mut lines = Vec::new();
+
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split(delimiter.as_str()).collect();
+            let kept: Vec<&str> = field_indexes
+                .iter()
+                .filter_map(|index| fields.get(*index as usize).copied())
+                .collect();
+
+            lines.push(kept.join(delimiter));
+        }
+
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+
+        Ok(Value::String(join_lines(&lines, text)))
+    }
+}
+
+pub struct EndsWith;
+
+impl Macro for EndsWith {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "ends_with",
+            description: "Check if a string ends with a suffix.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let string = argument[0].as_string()?;
+        let suffix = argument[1].as_string()?;
+
+        Ok(Value::Boolean(string.ends_with(suffix.as_str())))
+    }
+}
+
+pub struct Format;
+
+impl Macro for Format {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "format",
+            description: "Replace each {} in a template with the next argument. Write {{ and }} for literal braces.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_list()?;
+
+        Error::expected_minimum_function_argument_amount(
+            self.info().identifier,
+            argument.len(),
+            1,
+        )?;
+
+        let template = argument[0].as_string()?;
+
+        Ok(Value::String(format_template(template, &argument[1..])?))
+    }
+}
+
+fn format_template(template: &str, arguments: &[Value]) -> Result<String> {
+    let mut output = String::with_capacity(template.len());
+    let mut arguments = arguments.iter();
+    let mut characters = template.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match (character, characters.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                output.push(character);
+                characters.next();
+            }
+            ('{', Some('}')) => {
+                let argument = arguments.next().ok_or_else(|| {
+                    Error::CustomMessage(format!(
+                        "The template \"{template}\" has more placeholders than arguments."
+                    ))
+                })?;
+
+                output.push_str(&argument.to_string());
+                characters.next();
+            }
+            ('{', _) | ('}', _) => {
+                return Err(Error::CustomMessage(format!(
+                    "The template \"{template}\" has an unmatched brace. Use {{{{ or }}}} for a literal brace."
+                )))
+            }
+            _ => output.push(character),
+        }
+    }
+
+    Ok(output)
+}
+
+pub struct PadLeft;
+
+impl Macro for PadLeft {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "pad_left",
+            description: "Pad the start of a string to a width with spaces or a given character.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        pad(self.info().identifier, argument, true)
+    }
+}
+
+pub struct PadRight;
+
+impl Macro for PadRight {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "pad_right",
+            description: "Pad the end of a string to a width with spaces or a given character.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        pad(self.info().identifier, argument, false)
+    }
+}
+
+/// Shared implementation of `pad_left` and `pad_right`. The width is counted in terminal
+/// columns, ignoring ANSI escape codes.
+fn pad(identifier: &str, argument: &Value, left: bool) -> Result<Value> {
+    let argument = argument.as_list()?;
+
+    Error::expected_minimum_function_argument_amount(identifier, argument.len(), 2)?;
+
+    if argument.len() > 3 {
+        return Err(Error::ExpectedFunctionArgumentAmount {
+            identifier: identifier.to_string(),
+            expected: 3,
+            actual: argument.len(),
+        });
+    }
+
+    let string = argument[0].as_string()?;
+    let width = argument[1].as_int()?.max(0) as usize;
+    let fill = match argument.get(2) {
+        Some(fill) => {
+            let fill = fill.as_string()?;
+            let mut characters = fill.chars();
+
+            match (characters.next(), characters.next()) {
+                (Some(character), None) => character,
+                _ => {
+                    return Err(Error::CustomMessage(format!(
+                        "{identifier} needs a single fill character, not \"{fill}\"."
+                    )))
+                }
+            }
+        }
+        None => ' ',
+    };
+    let padding_count = width.saturating_sub(display_width(string));
+
+    match fill.len_utf8().checked_mul(padding_count) {
+        Some(length) if length <= MAX_STRING_LENGTH => {}
+        _ => {
+            return Err(Error::CustomMessage(format!(
+                "{identifier} cannot pad to a width of {width}, the limit is {MAX_STRING_LENGTH} bytes."
+            )))
+        }
+    }
+
+    let padding = fill.to_string().repeat(padding_count);
+
+    if left {
+        Ok(Value::String(padding + string))
+    } else {
+        Ok(Value::String(string.clone() + &padding))
+    }
+}
+
+pub struct RepeatString;
+
+impl Macro for RepeatString {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "repeat_string",
+            description: "Repeat a string the given number of times.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let string = argument[0].as_string()?;
+        let count = Error::expect_count(self.info().identifier, argument[1].as_int()?)?;
+
+        match string.len().checked_mul(count) {
+            Some(length) if length <= MAX_STRING_LENGTH => Ok(Value::String(string.repeat(count))),
+            _ => Err(Error::CustomMessage(format!(
+                "Repeating the string {count} times would make it longer than {MAX_STRING_LENGTH} bytes."
+            ))),
+        }
+    }
+}
+
+pub struct SortLines;
+
+impl Macro for SortLines {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "sort_lines",
+            description: "Sort the lines of a string.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let text = argument.as_string()?;
+        let mut lines: Vec<&str> = text.lines().collect();
+
+        lines.sort_unstable();
+
+        Ok(Value::String(join_lines(&lines, text)))
+    }
+}
+
+pub struct StartsWith;
+
+impl Macro for StartsWith {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "starts_with",
+            description: "Check if a string starts with a prefix.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let string = argument[0].as_string()?;
+        let prefix = argument[1].as_string()?;
+
+        Ok(Value::Boolean(string.starts_with(prefix.as_str())))
+    }
+}
+
+pub struct StripAnsi;
+
+impl Macro for StripAnsi {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "strip_ansi",
+            description: "Remove ANSI escape codes, like terminal colors, from a string.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let text = argument.as_string()?;
+
+        Ok(Value::String(strip_ansi_escapes::strip_str(text)))
+    }
+}
+
+pub struct Underline;
+
+impl Macro for Underline {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "underline",
+            description: "Underline a string in the terminal.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let text = argument.as_string()?;
+
+        Ok(Value::String(paint(text, Style::new().underline())))
+    }
+}
+
+pub struct UniqLines;
+
+impl Macro for UniqLines {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "uniq_lines",
+            description: "Collapse repeated adjacent lines of a string. Add `true` after the string for a table of lines and their counts.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let (text, with_counts) = if let Ok(text) = argument.as_string() {
+            (text, false)
+        } else {
+            let argument = argument.as_fixed_len_list(2)?;
+
+            (argument[0].as_string()?, argument[1].as_boolean()?)
+        };
+        let mut groups: Vec<(&str, i64)> = Vec::new();
+
+        for line in text.lines() {
+            match groups.last_mut() {
+                Some((last, count)) if *last == line => *count += 1,
+                _ => groups.push((line, 1)),
+            }
+        }
+
+        if with_counts {
+            let mut table = Table::new(vec!["count".to_string(), "line".to_string()]);
+
+            for (line, count) in groups {
+                table.insert(vec![Value::Integer(count), Value::String(line.to_string())])?;
+            }
+
+            Ok(Value::Table(table))
+        } else {
+            let lines: Vec<&str> = groups.into_iter().map(|(line, _)| line).collect();
+
+            Ok(Value::String(join_lines(&lines, text)))
+        }
+    }
+}
+
+pub struct Wc;
+
+impl Macro for Wc {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "wc",
+            description: "Count the lines, words and characters in a string.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let text = argument.as_string()?;
+
+        count_text(text)
+    }
+}
+
+pub struct WcFile;
+
+impl Macro for WcFile {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "wc_file",
+            description: "Count the lines, words and characters in a file.",
+            group: "string",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let path = argument.as_string()?;
+        let text = fs::read_to_string(path)?;
+
+        count_text(&text)
+    }
+}
+
+/// Applies a terminal style unless colors are turned off with `NO_COLOR`.
+fn paint(text: &str, style: Style) -> String {
+    style_text(text, style, colors_enabled(env::var("NO_COLOR").ok()))
+}
+
+fn style_text(text: &str, style: Style, colors_enabled: bool) -> String {
+    if colors_enabled {
+        style.paint(text).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Any non-empty `NO_COLOR` value disables colors, see https://no-color.org.
+fn colors_enabled(no_color: Option<String>) -> bool {
+    !matches!(no_color, Some(value) if !value.is_empty())
+}
+
+/// Joins lines with newlines, keeping the trailing newline if the original text had one.
+fn join_lines(lines: &[&str], original: &str) -> String {
+    let mut joined = lines.join("\n");
+
+    if original.ends_with('\n') && !lines.is_empty() {
+        joined.push('\n');
+    }
+
+    joined
+}
+
+/// Builds the map returned by `wc`. Words are separated by whitespace and
+/// characters are counted as Unicode scalar values rather than bytes.
+fn count_text(text: &str) -> Result<Value> {
+    let mut map = VariableMap::new();
+
+    map.set_value("lines", Value::Integer(text.lines().count() as i64))?;
+    map.set_value(
+        "words",
+        Value::Integer(text.split_whitespace().count() as i64),
+    )?;
+    map.set_value("characters", Value::Integer(text.chars().count() as i64))?;
+
+    Ok(Value::Map(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(lines: i64, words: i64, characters: i64) -> Value {
+        let mut map = VariableMap::new();
+
+        map.set_value("lines", Value::Integer(lines)).unwrap();
+        map.set_value("words", Value::Integer(words)).unwrap();
+        map.set_value("characters", Value::Integer(characters))
+            .unwrap();
+
+        Value::Map(map)
+    }
+
+    #[test]
+    fn cut() {
+        let text = Value::String("id\tname\tage\n1\tfoo\t20\n2\tbar\t30\n".to_string());
+        let arguments = Value::List(vec![
+            text,
+            Value::String("\t".to_string()),
+            Value::List(vec![Value::Integer(0), Value::Integer(2)]),
+        ]);
+
+        assert_eq!(
+            Ok(Value::String("id\tage\n1\t20\n2\t30\n".to_string())),
+            Cut.run(&arguments)
+        );
+    }
+
+    #[test]
+    fn cut_negative_field() {
+        let arguments = Value::List(vec![
+            Value::String("a,b,c".to_string()),
+            Value::String(",".to_string()),
+            Value::List(vec![Value::Integer(0), Value::Integer(-1)]),
+        ]);
+
+        assert!(matches!(Cut.run(&arguments), Err(Error::MacroFailure(_))));
+    }
+
+    #[test]
+    fn sort_lines() {
+        let text = Value::String("pear\napple\nfig\napple\n".to_string());
+
+        assert_eq!(
+            Ok(Value::String("apple\napple\nfig\npear\n".to_string())),
+            SortLines.run(&text)
+        );
+    }
+
+    #[test]
+    fn uniq_lines() {
+        let text = Value::String("a\na\nb\na\nc\nc".to_string());
+
+        assert_eq!(
+            Ok(Value::String("a\nb\na\nc".to_string())),
+            UniqLines.run(&text)
+        );
+
+        let mut expected = Table::new(vec!["count".to_string(), "line".to_string()]);
+
+        for (count, line) in [(2, "a"), (1, "b"), (1, "a"), (2, "c")] {
+            expected
+                .insert(vec![Value::Integer(count), Value::String(line.to_string())])
+                .unwrap();
+        }
+
+        assert_eq!(
+            Ok(Value::Table(expected)),
+            UniqLines.run(&Value::List(vec![text, Value::Boolean(true)]))
+        );
+    }
+
+    #[test]
+    fn wc() {
+        let text = Value::String("one two\nthree\ncafé\n".to_string());
+
+        assert_eq!(Ok(counts(3, 4, 19)), Wc.run(&text));
+    }
+
+    #[test]
+    fn wc_empty() {
+        assert_eq!(Ok(counts(0, 0, 0)), Wc.run(&Value::String(String::new())));
+    }
+
+    #[test]
+    fn wc_file() {
+        let path = "./target/wc_file.txt";
+
+        fs::write(path, "one two\nthree\n").unwrap();
+
+        assert_eq!(
+            Ok(counts(2, 3, 14)),
+            WcFile.run(&Value::String(path.to_string()))
+        );
+    }
+
+    #[test]
+    fn color() {
+        let styled = style_text("hiya", AnsiColor::Red.normal(), true);
+
+        assert_eq!("\x1b[31mhiya\x1b[0m", styled);
+        assert!(Color
+            .run(&Value::List(vec![
+                Value::String("hiya".to_string()),
+                Value::String("chartreuse".to_string()),
+            ]))
+            .is_err());
+    }
+
+    #[test]
+    fn no_color() {
+        assert!(colors_enabled(None));
+        assert!(colors_enabled(Some(String::new())));
+        assert!(!colors_enabled(Some("1".to_string())));
+        assert_eq!(
+            "hiya",
+            style_text("hiya", Style::new().bold().underline(), false)
+        );
+    }
+
+    #[test]
+    fn strip_ansi() {
+        let text = Value::String("\x1b[1;31merror:\x1b[0m not \x1b[4mfound\x1b[0m\n".to_string());
+
+        assert_eq!(
+            Ok(Value::String("error: not found\n".to_string())),
+            StripAnsi.run(&text)
+        );
+    }
+
+    #[test]
+    fn box_single_line() {
+        let result = BoxText.run(&Value::String("hiya".to_string()));
+
+        assert_eq!(
+            Ok(Value::String("┌──────┐\n│ hiya │\n└──────┘".to_string())),
+            result
+        );
+    }
+
+    #[test]
+    fn box_multi_line() {
+        let result = BoxText.run(&Value::String("whale\nis\nbig\n".to_string()));
+
+        assert_eq!(
+            Ok(Value::String(
+                "┌───────┐\n│ whale │\n│ is    │\n│ big   │\n└───────┘".to_string()
+            )),
+            result
+        );
+    }
+
+    #[test]
+    fn box_wide_and_colored_text() {
+        let colored = paint("hi", Style::new().bold());
+        let result = BoxText.run(&Value::String(format!("鯨\n{colored}\nx")));
+
+        assert_eq!(
+            Ok(Value::String(format!(
+                "┌────┐\n│ 鯨 │\n│ {colored} │\n│ x  │\n└────┘"
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn format() {
+        let result = Format.run(&Value::List(vec![
+            Value::String("{} and {}".to_string()),
+            Value::String("a".to_string()),
+            Value::String("b".to_string()),
+        ]));
+
+        assert_eq!(Ok(Value::String("a and b".to_string())), result);
+    }
+
+    #[test]
+    fn format_escaped_braces() {
+        assert_eq!(
+            Ok("{1} = 1".to_string()),
+            format_template("{{{}}} = {}", &[Value::Integer(1), Value::Integer(1)])
+        );
+        assert!(format_template("{} and {}", &[Value::Integer(1)]).is_err());
+        assert!(format_template("{oops}", &[]).is_err());
+    }
+
+    #[test]
+    fn starts_and_ends_with() {
+        let arguments = |string: &str, affix: &str| {
+            Value::List(vec![
+                Value::String(string.to_string()),
+                Value::String(affix.to_string()),
+            ])
+        };
+
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            StartsWith.run(&arguments("whale.ds", "whale"))
+        );
+        assert_eq!(
+            Ok(Value::Boolean(false)),
+            StartsWith.run(&arguments("whale.ds", ".ds"))
+        );
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            EndsWith.run(&arguments("whale.ds", ".ds"))
+        );
+        assert_eq!(
+            Ok(Value::Boolean(false)),
+            EndsWith.run(&arguments("whale.ds", ".toml"))
+        );
+        assert_eq!(
+            Err(Error::ExpectedString {
+                actual: Value::Integer(1)
+            }),
+            EndsWith.run(&Value::List(vec![
+                Value::Integer(1),
+                Value::String("1".to_string())
+            ]))
+        );
+    }
+
+    #[test]
+    fn repeat_string() {
+        let repeat = |count: i64| {
+            RepeatString.run(&Value::List(vec![
+                Value::String("ab".to_string()),
+                Value::Integer(count),
+            ]))
+        };
+
+        assert_eq!(Ok(Value::String("ababab".to_string())), repeat(3));
+        assert_eq!(Ok(Value::String(String::new())), repeat(0));
+        assert!(matches!(repeat(-2), Err(Error::CustomMessage(_))));
+        assert!(matches!(repeat(i64::MAX), Err(Error::CustomMessage(_))));
+        assert!(matches!(
+            repeat(MAX_STRING_LENGTH as i64),
+            Err(Error::CustomMessage(_))
+        ));
+    }
+
+    #[test]
+    fn pad_left_and_right() {
+        assert_eq!(
+            Ok(Value::String("007".to_string())),
+            PadLeft.run(&Value::List(vec![
+                Value::String("7".to_string()),
+                Value::Integer(3),
+                Value::String("0".to_string()),
+            ]))
+        );
+        assert_eq!(
+            Ok(Value::String("ab   ".to_string())),
+            PadRight.run(&Value::List(vec![
+                Value::String("ab".to_string()),
+                Value::Integer(5),
+            ]))
+        );
+        assert_eq!(
+            Ok(Value::String("whale".to_string())),
+            PadLeft.run(&Value::List(vec![
+                Value::String("whale".to_string()),
+                Value::Integer(3),
+            ]))
+        );
+    }
+
+    #[test]
+    fn pad_uses_display_width() {
+        assert_eq!(
+            Ok(Value::String("\u{1b}[31mab\u{1b}[0m  ".to_string())),
+            PadRight.run(&Value::List(vec![
+                Value::String("\u{1b}[31mab\u{1b}[0m".to_string()),
+                Value::Integer(4),
+            ]))
+        );
+        assert_eq!(
+            Ok(Value::String("-鯨".to_string())),
+            PadLeft.run(&Value::List(vec![
+                Value::String("鯨".to_string()),
+                Value::Integer(3),
+                Value::String("-".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn pad_too_many_arguments() {
+        let result = PadLeft.run(&Value::List(vec![
+            Value::String("7".to_string()),
+            Value::Integer(3),
+            Value::String("0".to_string()),
+            Value::String("0".to_string()),
+        ]));
+
+        assert!(matches!(
+            result,
+            Err(Error::ExpectedFunctionArgumentAmount { actual: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn pad_width_is_bounded() {
+        let result = PadRight.run(&Value::List(vec![
+            Value::String("ab".to_string()),
+            Value::Integer(i64::MAX),
+            Value::String("é".to_string()),
+        ]));
+
+        assert!(matches!(result, Err(Error::CustomMessage(_))));
+    }
+}
@@ -1,6 +1,10 @@
+use std::{collections::BTreeMap, env, path::Path};
+
 use sys_info::cpu_speed;
+use sysinfo::{System, SystemExt};
+use terminal_size::{Height, Width};
 
-use crate::{Macro, MacroInfo, Result, Value};
+use crate::{Error, Macro, MacroInfo, Result, Table, Value, VariableMap};
 
 pub struct CpuSpeed;
 
@@ -21,3 +25,238 @@ impl Macro for CpuSpeed {
         Ok(Value::Integer(speed))
     }
 }
+
+pub struct Memory;
+
+impl Macro for Memory {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "memory",
+            description: "Return a table of the total, used, free and available memory in bytes.",
+            group: "system",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        argument.as_empty()?;
+
+        let mut system = System::new();
+
+        system.refresh_memory();
+
+        if !System::IS_SUPPORTED || system.total_memory() == 0 {
+            return Err(Error::MacroFailure(
+                "Memory usage is not available on this platform.".to_string(),
+            ));
+        }
+
+        let mut table = Table::new(vec![
+            "total".to_string(),
+            "used".to_string(),
+            "free".to_string(),
+            "available".to_string(),
+        ]);
+
+        table.insert(vec![
+            Value::Integer(system.total_memory() as i64),
+            Value::Integer(system.used_memory() as i64),
+            Value::Integer(system.free_memory() as i64),
+            Value::Integer(system.available_memory() as i64),
+        ])?;
+
+        Ok(Value::Table(table))
+    }
+}
+
+pub struct SystemEnv;
+
+impl Macro for SystemEnv {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "system_env",
+            description: "Return a map of every environment variable.",
+            group: "system",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        argument.as_empty()?;
+
+        let variables = env::vars_os()
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().to_string(),
+                    Value::String(value.to_string_lossy().to_string()),
+                )
+            })
+            .collect::<BTreeMap<String, Value>>();
+
+        Ok(Value::Map(VariableMap::from(variables)))
+    }
+}
+
+pub struct SystemInfo;
+
+impl Macro for SystemInfo {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "system_info",
+            description: "Get the hostname, operating system, kernel version and memory usage.",
+            group: "system",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        argument.as_empty()?;
+
+        let mut system = System::new();
+
+        system.refresh_memory();
+
+        let string_or_empty = |string: Option<String>| string.map_or(Value::Empty, Value::String);
+        let mut map = VariableMap::new();
+
+        map.set_value("hostname", string_or_empty(system.host_name()))?;
+        map.set_value("os", string_or_empty(system.long_os_version()))?;
+        map.set_value("kernel_version", string_or_empty(system.kernel_version()))?;
+        map.set_value("total_memory", Value::Integer(system.total_memory() as i64))?;
+        map.set_value("used_memory", Value::Integer(system.used_memory() as i64))?;
+
+        Ok(Value::Map(map))
+    }
+}
+
+pub struct Open;
+
+impl Macro for Open {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "open",
+            description: "Open a file or URL with the default application.",
+            group: "system",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        let target = argument.as_string()?;
+
+        check_open_target(target)?;
+        open::that(target).map_err(|error| Error::MacroFailure(error.to_string()))?;
+
+        Ok(Value::Empty)
+    }
+}
+
+/// URLs are passed through as-is, anything else must be an existing path.
+fn check_open_target(target: &str) -> Result<()> {
+    if target.contains("://") || Path::new(target).exists() {
+        Ok(())
+    } else {
+        Err(Error::MacroFailure(format!(
+            "Cannot open {target} because it does not exist."
+        )))
+    }
+}
+
+pub struct TerminalSize;
+
+impl Macro for TerminalSize {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "terminal_size",
+            description:
+                "Return the terminal's columns and rows, or 80 by 24 if there is no terminal.",
+            group: "system",
+        }
+    }
+
+    fn run(&self, argument: &Value) -> Result<Value> {
+        argument.as_empty()?;
+
+        let (columns, rows) = size_or_default(terminal_size::terminal_size());
+        let mut size = VariableMap::new();
+
+        size.set_value("columns", Value::Integer(columns as i64))?;
+        size.set_value("rows", Value::Integer(rows as i64))?;
+
+        Ok(Value::Map(size))
+    }
+}
+
+fn size_or_default(size: Option<(Width, Height)>) -> (u16, u16) {
+    match size {
+        Some((Width(columns), Height(rows))) => (columns, rows),
+        None => (80, 24),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_env() {
+        env::set_var("WHALE_SYSTEM_ENV_TEST", "hiya");
+
+        let result = SystemEnv.run(&Value::Empty).unwrap();
+        let value = result
+            .as_map()
+            .unwrap()
+            .get_value("WHALE_SYSTEM_ENV_TEST")
+            .unwrap();
+
+        assert_eq!(Some(Value::String("hiya".to_string())), value);
+    }
+
+    #[test]
+    fn system_env_keeps_dotted_keys() {
+        env::set_var("WHALE_SYSTEM_ENV_DOTTED", "outer");
+        env::set_var("WHALE_SYSTEM_ENV_DOTTED.INNER", "inner");
+
+        let result = SystemEnv.run(&Value::Empty).unwrap();
+        let variables = result.as_map().unwrap().inner();
+
+        assert_eq!(
+            Some(&Value::String("outer".to_string())),
+            variables.get("WHALE_SYSTEM_ENV_DOTTED")
+        );
+        assert_eq!(
+            Some(&Value::String("inner".to_string())),
+            variables.get("WHALE_SYSTEM_ENV_DOTTED.INNER")
+        );
+    }
+
+    #[test]
+    fn system_info() {
+        let result = SystemInfo.run(&Value::Empty).unwrap();
+        let hostname = result.as_map().unwrap().get_value("hostname").unwrap();
+
+        assert!(!hostname.unwrap().as_string().unwrap().is_empty());
+    }
+
+    #[test]
+    fn memory() {
+        let result = Memory.run(&Value::Empty).unwrap();
+        let table = result.as_table().unwrap();
+
+        assert_eq!(1, table.len());
+        assert!(table.get(0).unwrap()[0].as_int().unwrap() > 0);
+    }
+
+    #[test]
+    fn open_checks_argument() {
+        assert!(Open.run(&Value::Integer(1)).is_err());
+        assert!(matches!(
+            Open.run(&Value::String("./target/no_such_file_to_open".to_string())),
+            Err(Error::MacroFailure(_))
+        ));
+        assert!(check_open_target("https://example.com").is_ok());
+        assert!(check_open_target("./target").is_ok());
+    }
+
+    #[test]
+    fn terminal_size_without_terminal() {
+        assert_eq!((80, 24), size_or_default(None));
+        assert_eq!((120, 40), size_or_default(Some((Width(120), Height(40)))));
+    }
+}
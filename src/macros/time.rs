@@ -1,6 +1,11 @@
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use crate::{Macro, MacroInfo, Result, Time, Value};
+use chrono::{
+    format::{Item, StrftimeItems},
+    NaiveDate, NaiveDateTime,
+};
+
+use crate::{Error, Macro, MacroInfo, Result, Time, Value};
 
 pub struct Now;
 
@@ -22,6 +27,26 @@ impl Macro for Now {
     }
 }
 
+pub struct NowUnix;
+
+impl Macro for NowUnix {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "now_unix",
+            description: "Return the number of seconds since the Unix epoch.",
+            group: "time",
+        }
+    }
+
+    fn run(&self, argument: &crate::Value) -> Result<Value> {
+        argument.as_empty()?;
+
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+        Ok(Value::Integer(seconds as i64))
+    }
+}
+
 pub struct Local;
 
 impl Macro for Local {
@@ -39,3 +64,186 @@ impl Macro for Local {
         Ok(Value::String(argument.as_local()))
     }
 }
+
+pub struct Duration;
+
+impl Macro for Duration {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "duration",
+            description:
+                "Show a number of seconds as hours, minutes and seconds, like \"2h 3m 4s\".",
+            group: "time",
+        }
+    }
+
+    fn run(&self, argument: &crate::Value) -> Result<Value> {
+        let seconds = argument.as_int()?;
+
+        if seconds < 0 {
+            return Err(Error::CustomMessage(format!(
+                "Cannot show a negative duration of {seconds} seconds."
+            )));
+        }
+
+        let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+        let mut parts = Vec::new();
+
+        if hours > 0 {
+            parts.push(format!("{hours}h"));
+        }
+        if minutes > 0 {
+            parts.push(format!("{minutes}m"));
+        }
+        if seconds > 0 || parts.is_empty() {
+            parts.push(format!("{seconds}s"));
+        }
+
+        Ok(Value::String(parts.join(" ")))
+    }
+}
+
+pub struct FormatTime;
+
+impl Macro for FormatTime {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "format_time",
+            description: "Format a time value with a strftime pattern like \"%Y-%m-%d\".",
+            group: "time",
+        }
+    }
+
+    fn run(&self, argument: &crate::Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let time = match &argument[0] {
+            Value::Time(time) => time,
+            value => {
+                return Err(Error::CustomMessage(format!(
+                    "format_time expected a time value, but got {value}."
+                )))
+            }
+        };
+        let pattern = argument[1].as_string()?;
+        // Drop unknown specifiers, which would otherwise make formatting panic.
+        let items = StrftimeItems::new(pattern).filter(|item| *item != Item::Error);
+
+        Ok(Value::String(
+            time.as_date_time().format_with_items(items).to_string(),
+        ))
+    }
+}
+
+pub struct ParseTime;
+
+impl Macro for ParseTime {
+    fn info(&self) -> MacroInfo<'static> {
+        MacroInfo {
+            identifier: "parse_time",
+            description:
+                "Read a time value from a string with a strftime pattern like \"%Y-%m-%d\".",
+            group: "time",
+        }
+    }
+
+    fn run(&self, argument: &crate::Value) -> Result<Value> {
+        let argument = argument.as_fixed_len_list(2)?;
+        let string = argument[0].as_string()?;
+        let pattern = argument[1].as_string()?;
+
+        // Patterns without a time of day, like "%Y-%m-%d", are read as midnight.
+        let naive = NaiveDateTime::parse_from_str(string, pattern).or_else(|error| {
+            NaiveDate::parse_from_str(string, pattern)
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+                .map_err(|_| Error::CustomMessage(format!("Cannot parse {string}: {error}")))
+        })?;
+
+        Ok(Value::Time(Time::from_naive(naive)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn now_unix() {
+        let before = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let now = NowUnix.run(&Value::Empty).unwrap().as_int().unwrap();
+
+        // 2023-01-01T00:00:00Z
+        assert!(now > 1_672_531_200);
+        assert!(now >= before && now - before <= 1);
+    }
+
+    #[test]
+    fn format_time() {
+        // 2023-07-14T02:40:00Z
+        let time = Time::from_timestamp(1_689_302_400_000_000);
+        let result = FormatTime.run(&Value::List(vec![
+            Value::Time(time),
+            Value::String("%Y-%m-%d".to_string()),
+        ]));
+
+        assert_eq!(Ok(Value::String("2023-07-14".to_string())), result);
+
+        let result = FormatTime.run(&Value::List(vec![
+            Value::Time(time),
+            Value::String("%Y %Q".to_string()),
+        ]));
+
+        assert_eq!(Ok(Value::String("2023 ".to_string())), result);
+
+        let result = FormatTime.run(&Value::List(vec![
+            Value::Integer(0),
+            Value::String("%Y-%m-%d".to_string()),
+        ]));
+
+        assert!(matches!(result, Err(Error::CustomMessage(_))));
+    }
+
+    #[test]
+    fn parse_time() {
+        let pattern = Value::String("%Y-%m-%d".to_string());
+        let time = ParseTime
+            .run(&Value::List(vec![
+                Value::String("2023-01-15".to_string()),
+                pattern.clone(),
+            ]))
+            .unwrap();
+        let formatted = FormatTime.run(&Value::List(vec![time, pattern.clone()]));
+
+        assert_eq!(Ok(Value::String("2023-01-15".to_string())), formatted);
+
+        let result = ParseTime.run(&Value::List(vec![
+            Value::String("January 15th".to_string()),
+            pattern,
+        ]));
+
+        assert!(matches!(result, Err(Error::CustomMessage(_))));
+    }
+
+    #[test]
+    fn duration() {
+        for (seconds, expected) in [
+            (0, "0s"),
+            (59, "59s"),
+            (3600, "1h"),
+            (7384, "2h 3m 4s"),
+            (90061, "25h 1m 1s"),
+        ] {
+            assert_eq!(
+                Ok(Value::String(expected.to_string())),
+                Duration.run(&Value::Integer(seconds))
+            );
+        }
+
+        assert!(matches!(
+            Duration.run(&Value::Integer(-1)),
+            Err(Error::CustomMessage(_))
+        ));
+    }
+}
@@ -19,7 +19,8 @@ use std::{
 };
 
 use whale_lib::{
-    eval, eval_with_context, Macro, MacroInfo, Result, Value, VariableMap, MACRO_LIST,
+    clear_interrupt, eval, eval_with_context, set_interrupt_handler, Macro, MacroInfo, Result,
+    Value, VariableMap, MACRO_LIST,
 };
 
 /// Command-line arguments to be parsed.
@@ -36,14 +37,19 @@ struct Args {
 
     #[arg(short, long)]
     gui: bool,
+
+    /// Arguments for the file being run, available to it as `args`.
+    #[arg(trailing_var_arg = true)]
+    args: Vec<String>,
 }
 
 fn main() {
     let args = Args::parse();
 
+    set_interrupt_handler();
+
     let eval_result = if let Some(path) = args.path {
-        let file_contents = read_to_string(path).unwrap();
-        eval(&file_contents)
+        run_file(&path, args.args)
     } else if let Some(command) = args.command {
         eval(&command)
     } else if args.gui {
@@ -62,6 +68,17 @@ fn main() {
     }
 }
 
+/// Run a whale file with the given arguments bound to `args` as a list of strings.
+fn run_file(path: &str, arguments: Vec<String>) -> Result<Value> {
+    let file_contents = read_to_string(path)?;
+    let mut context = VariableMap::new();
+    let arguments = arguments.into_iter().map(Value::String).collect();
+
+    context.set_value("args", Value::List(arguments))?;
+
+    eval_with_context(&file_contents, &mut context)
+}
+
 pub struct Gui {
     text_edit_buffer: String,
     whale_context: VariableMap,
@@ -237,6 +254,9 @@ fn run_gui_shell() {
 
 fn run_cli_shell() {
     let mut context = VariableMap::new();
+
+    context.set_show_spinner(true);
+
     let mut line_editor = setup_reedline();
     let prompt = DefaultPrompt {
         left_prompt: DefaultPromptSegment::WorkingDirectory,
@@ -248,6 +268,8 @@ fn run_cli_shell() {
 
         match sig {
             Ok(Signal::Success(buffer)) => {
+                clear_interrupt();
+
                 let eval_result = eval_with_context(&buffer, &mut context);
 
                 match eval_result {
@@ -432,3 +454,25 @@ fn setup_reedline() -> Reedline {
         .with_partial_completions(true)
         .with_quick_completions(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_file_with_args() {
+        let path = "./target/run_file_with_args.whale";
+
+        fs::write(path, "args").unwrap();
+
+        let result = run_file(path, vec!["foo".to_string(), "bar".to_string()]);
+
+        assert_eq!(
+            Ok(Value::List(vec![
+                Value::String("foo".to_string()),
+                Value::String("bar".to_string()),
+            ])),
+            result
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
 
 use crate::{error::*, value::Value, Result, VariableMap};
 
@@ -19,11 +22,24 @@ pub enum Operator {
     Mul,
     /// A binary division operator.
     Div,
+    /// A binary division operator that rounds toward negative infinity.
+    FloorDiv,
     /// A binary modulo operator.
     Mod,
     /// A binary exponentiation operator.
     Exp,
 
+    /// A binary bitwise and operator.
+    BitAnd,
+    /// A binary bitwise or operator.
+    BitOr,
+    /// A binary bitwise exclusive or operator.
+    BitXor,
+    /// A binary left shift operator.
+    Shl,
+    /// A binary right shift operator.
+    Shr,
+
     /// A binary equality comparator.
     Eq,
     /// A binary inequality comparator.
@@ -45,6 +61,8 @@ pub enum Operator {
 
     /// A binary assignment operator.
     Assign,
+    /// A binary assignment operator that binds a constant, written as `const name = value`.
+    ConstAssign,
     /// A binary add-assign operator.
     AddAssign,
     /// A binary subtract-assign operator.
@@ -115,16 +133,21 @@ impl Operator {
 
             Add | Sub => 95,
             Neg => 110,
-            Mul | Div | Mod => 100,
+            Mul | Div | FloorDiv | Mod => 100,
             Exp => 120,
 
+            Shl | Shr => 90,
+            BitAnd => 87,
+            BitXor => 86,
+            BitOr => 85,
+
             Eq | Neq | Gt | Lt | Geq | Leq => 80,
             And => 75,
             Or => 70,
             Not => 110,
 
-            Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
-            | AndAssign | OrAssign => 50,
+            Assign | ConstAssign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign
+            | ExpAssign | AndAssign | OrAssign => 50,
 
             Tuple => 40,
             Chain => 0,
@@ -140,7 +163,7 @@ impl Operator {
     /// Left-to-right chaining has priority if operators with different order but same precedence are chained.
     pub(crate) const fn is_left_to_right(&self) -> bool {
         use crate::operator::Operator::*;
-        !matches!(self, Assign | FunctionIdentifier { .. })
+        !matches!(self, Assign | ConstAssign | FunctionIdentifier { .. })
     }
 
     /// Returns true if chains of this operator should be flattened into one operator with many arguments.
@@ -159,9 +182,11 @@ impl Operator {
     pub(crate) const fn max_argument_amount(&self) -> Option<usize> {
         use crate::operator::Operator::*;
         match self {
-            Add | Sub | Mul | Div | Mod | Exp | Eq | Neq | Gt | Lt | Geq | Leq | And | Or
-            | Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
-            | AndAssign | OrAssign => Some(2),
+            Add | Sub | Mul | Div | FloorDiv | Mod | Exp | BitAnd | BitOr | BitXor | Shl | Shr
+            | Eq | Neq | Gt | Lt | Geq | Leq | And | Or | Assign | ConstAssign | AddAssign
+            | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign | AndAssign | OrAssign => {
+                Some(2)
+            }
             Tuple | Chain => None,
             Not | Neg | RootNode => Some(1),
             Const { .. } => Some(0),
@@ -189,6 +214,17 @@ impl Operator {
             }
             Add => {
                 Error::expect_operator_argument_amount(arguments.len(), 2)?;
+
+                if let (Value::Time(time), Value::Integer(seconds)) = (&arguments[0], &arguments[1])
+                {
+                    return time
+                        .checked_add_seconds(*seconds)
+                        .map(Value::Time)
+                        .ok_or_else(|| {
+                            Error::addition_error(arguments[0].clone(), arguments[1].clone())
+                        });
+                }
+
                 expect_number_or_string(&arguments[0])?;
                 expect_number_or_string(&arguments[1])?;
 
@@ -222,6 +258,23 @@ impl Operator {
             }
             Sub => {
                 Error::expect_operator_argument_amount(arguments.len(), 2)?;
+
+                match (&arguments[0], &arguments[1]) {
+                    (Value::Time(time), Value::Integer(seconds)) => {
+                        return seconds
+                            .checked_neg()
+                            .and_then(|seconds| time.checked_add_seconds(seconds))
+                            .map(Value::Time)
+                            .ok_or_else(|| {
+                                Error::subtraction_error(arguments[0].clone(), arguments[1].clone())
+                            });
+                    }
+                    (Value::Time(time), Value::Time(earlier)) => {
+                        return Ok(Value::Integer(time.seconds_since(earlier)));
+                    }
+                    _ => {}
+                }
+
                 arguments[0].as_number()?;
                 arguments[1].as_number()?;
 
@@ -283,6 +336,12 @@ impl Operator {
                 arguments[1].as_number()?;
 
                 if let (Ok(a), Ok(b)) = (arguments[0].as_int(), arguments[1].as_int()) {
+                    if b == 0 {
+                        return Err(Error::DivisionByZero {
+                            dividend: arguments[0].clone(),
+                        });
+                    }
+
                     let result = a.checked_div(b);
                     if let Some(result) = result {
                         Ok(Value::Integer(result))
@@ -298,12 +357,46 @@ impl Operator {
                     ))
                 }
             }
+            FloorDiv => {
+                Error::expect_operator_argument_amount(arguments.len(), 2)?;
+                arguments[0].as_number()?;
+                arguments[1].as_number()?;
+
+                if let (Ok(a), Ok(b)) = (arguments[0].as_int(), arguments[1].as_int()) {
+                    if b == 0 {
+                        return Err(Error::DivisionByZero {
+                            dividend: arguments[0].clone(),
+                        });
+                    }
+
+                    let quotient = a.checked_div(b).ok_or_else(|| {
+                        Error::division_error(arguments[0].clone(), arguments[1].clone())
+                    })?;
+                    let rounds_toward_zero = a % b != 0 && (a < 0) != (b < 0);
+
+                    if rounds_toward_zero {
+                        Ok(Value::Integer(quotient - 1))
+                    } else {
+                        Ok(Value::Integer(quotient))
+                    }
+                } else {
+                    Ok(Value::Float(
+                        (arguments[0].as_number()? / arguments[1].as_number()?).floor(),
+                    ))
+                }
+            }
             Mod => {
                 Error::expect_operator_argument_amount(arguments.len(), 2)?;
                 arguments[0].as_number()?;
                 arguments[1].as_number()?;
 
                 if let (Ok(a), Ok(b)) = (arguments[0].as_int(), arguments[1].as_int()) {
+                    if b == 0 {
+                        return Err(Error::ModuloByZero {
+                            dividend: arguments[0].clone(),
+                        });
+                    }
+
                     let result = a.checked_rem(b);
                     if let Some(result) = result {
                         Ok(Value::Integer(result))
@@ -328,6 +421,35 @@ impl Operator {
                     arguments[0].as_number()?.powf(arguments[1].as_number()?),
                 ))
             }
+            BitAnd | BitOr | BitXor => {
+                Error::expect_operator_argument_amount(arguments.len(), 2)?;
+                let a = arguments[0].as_int()?;
+                let b = arguments[1].as_int()?;
+
+                Ok(Value::Integer(match self {
+                    BitAnd => a & b,
+                    BitOr => a | b,
+                    _ => a ^ b,
+                }))
+            }
+            Shl | Shr => {
+                Error::expect_operator_argument_amount(arguments.len(), 2)?;
+                let a = arguments[0].as_int()?;
+                let b = arguments[1].as_int()?;
+                let result = u32::try_from(b).ok().and_then(|b| {
+                    if matches!(self, Shl) {
+                        a.checked_shl(b)
+                    } else {
+                        a.checked_shr(b)
+                    }
+                });
+
+                result.map(Value::Integer).ok_or_else(|| {
+                    Error::CustomMessage(format!(
+                        "Cannot shift {a} {self} {b}, the shift amount must be between 0 and 63."
+                    ))
+                })
+            }
             Eq => {
                 Error::expect_operator_argument_amount(arguments.len(), 2)?;
 
@@ -418,8 +540,8 @@ impl Operator {
 
                 Ok(Value::Boolean(!a))
             }
-            Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
-            | AndAssign | OrAssign => Err(Error::ContextNotMutable),
+            Assign | ConstAssign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign
+            | ExpAssign | AndAssign | OrAssign => Err(Error::ContextNotMutable),
             Tuple => Ok(Value::List(arguments.into())),
             Chain => {
                 if arguments.is_empty() {
@@ -467,6 +589,13 @@ impl Operator {
 
                 Ok(Value::Empty)
             }
+            ConstAssign => {
+                Error::expect_operator_argument_amount(arguments.len(), 2)?;
+                let target = arguments[0].as_string()?;
+                context.set_constant(target, arguments[1].clone())?;
+
+                Ok(Value::Empty)
+            }
             AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign | AndAssign
             | OrAssign => {
                 Error::expect_operator_argument_amount(arguments.len(), 2)?;
@@ -496,6 +625,12 @@ impl Operator {
 
                 Ok(Value::Empty)
             }
+            FunctionIdentifier { identifier } => {
+                Error::expect_operator_argument_amount(arguments.len(), 1)?;
+                let arguments = &arguments[0];
+
+                context.call_function_mut(identifier, arguments)
+            }
             _ => self.eval(arguments, context),
         }
     }
@@ -511,9 +646,16 @@ impl Display for Operator {
             Neg => write!(f, "-"),
             Mul => write!(f, "*"),
             Div => write!(f, "/"),
+            FloorDiv => write!(f, "//"),
             Mod => write!(f, "%"),
             Exp => write!(f, "^"),
 
+            BitAnd => write!(f, "&"),
+            BitOr => write!(f, "|"),
+            BitXor => write!(f, "~"),
+            Shl => write!(f, "<<"),
+            Shr => write!(f, ">>"),
+
             Eq => write!(f, "=="),
             Neq => write!(f, "!="),
             Gt => write!(f, ">"),
@@ -525,6 +667,7 @@ impl Display for Operator {
             Not => write!(f, "!"),
 
             Assign => write!(f, " = "),
+            ConstAssign => write!(f, " = "),
             AddAssign => write!(f, " += "),
             SubAssign => write!(f, " -= "),
             MulAssign => write!(f, " *= "),
@@ -545,3 +688,141 @@ impl Display for Operator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Time;
+
+    use super::*;
+
+    fn time(seconds: i64) -> Value {
+        Value::Time(Time::from_timestamp(seconds * 1_000_000))
+    }
+
+    #[test]
+    fn add_seconds_to_time() {
+        let context = VariableMap::new();
+        let result = Operator::Add.eval(&[time(1_000), Value::Integer(60)], &context);
+
+        assert_eq!(Ok(time(1_060)), result);
+    }
+
+    #[test]
+    fn subtract_seconds_from_time() {
+        let context = VariableMap::new();
+        let result = Operator::Sub.eval(&[time(1_000), Value::Integer(60)], &context);
+
+        assert_eq!(Ok(time(940)), result);
+    }
+
+    #[test]
+    fn time_offset_out_of_range() {
+        let context = VariableMap::new();
+
+        for seconds in [i64::MAX, i64::MIN, i64::MAX / 1000].iter() {
+            let arguments = [time(1_000), Value::Integer(*seconds)];
+
+            assert_eq!(
+                Err(Error::addition_error(
+                    arguments[0].clone(),
+                    arguments[1].clone()
+                )),
+                Operator::Add.eval(&arguments, &context)
+            );
+            assert_eq!(
+                Err(Error::subtraction_error(
+                    arguments[0].clone(),
+                    arguments[1].clone()
+                )),
+                Operator::Sub.eval(&arguments, &context)
+            );
+        }
+    }
+
+    #[test]
+    fn subtract_times() {
+        let context = VariableMap::new();
+
+        assert_eq!(
+            Ok(Value::Integer(90)),
+            Operator::Sub.eval(&[time(1_090), time(1_000)], &context)
+        );
+        assert_eq!(
+            Ok(Value::Integer(-90)),
+            Operator::Sub.eval(&[time(1_000), time(1_090)], &context)
+        );
+    }
+
+    #[test]
+    fn monotonic_time_arithmetic() {
+        let context = VariableMap::new();
+        let start = Value::Time(Time::monotonic(std::time::Instant::now()));
+        let later = Operator::Add
+            .eval(&[start.clone(), Value::Integer(5)], &context)
+            .unwrap();
+
+        assert_eq!(
+            Ok(Value::Integer(5)),
+            Operator::Sub.eval(&[later.clone(), start.clone()], &context)
+        );
+        assert_eq!(
+            Ok(start),
+            Operator::Sub.eval(&[later, Value::Integer(5)], &context)
+        );
+    }
+
+    #[test]
+    fn integer_division_by_zero() {
+        let context = VariableMap::new();
+        let arguments = [Value::Integer(1), Value::Integer(0)];
+
+        assert_eq!(
+            Err(Error::DivisionByZero {
+                dividend: Value::Integer(1)
+            }),
+            Operator::Div.eval(&arguments, &context)
+        );
+        assert_eq!(
+            Err(Error::ModuloByZero {
+                dividend: Value::Integer(1)
+            }),
+            Operator::Mod.eval(&arguments, &context)
+        );
+        assert_eq!(
+            Err(Error::DivisionError {
+                dividend: Value::Integer(i64::MIN),
+                divisor: Value::Integer(-1)
+            }),
+            Operator::Div.eval(&[Value::Integer(i64::MIN), Value::Integer(-1)], &context)
+        );
+    }
+
+    #[test]
+    fn floor_division() {
+        assert_eq!(Ok(Value::Integer(3)), crate::eval("7 // 2"));
+        assert_eq!(Ok(Value::Integer(-4)), crate::eval("-7 // 2"));
+        assert_eq!(Ok(Value::Integer(-4)), crate::eval("7 // -2"));
+        assert_eq!(Ok(Value::Integer(2)), crate::eval("6 // 3"));
+        assert_eq!(Ok(Value::Float(-4.0)), crate::eval("-7.0 // 2"));
+        assert_eq!(Ok(Value::Float(3.5)), crate::eval("7.0 / 2"));
+        assert!(crate::eval("1 // 0").is_err());
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        assert_eq!(Ok(Value::Boolean(true)), crate::eval("6 & 3 == 2"));
+        assert_eq!(Ok(Value::Integer(7)), crate::eval("6 | 3"));
+        assert_eq!(Ok(Value::Integer(5)), crate::eval("6 ~ 3"));
+        assert_eq!(Ok(Value::Boolean(true)), crate::eval("1 << 4 == 16"));
+        assert_eq!(Ok(Value::Integer(4)), crate::eval("16 >> 2"));
+        assert_eq!(Ok(Value::Boolean(false)), crate::eval("true && false"));
+        assert_eq!(
+            Err(Error::ExpectedInt {
+                actual: Value::Float(1.5)
+            }),
+            crate::eval("1.5 & 1")
+        );
+        assert!(crate::eval("1 << 64").is_err());
+        assert!(crate::eval("1 << -1").is_err());
+    }
+}
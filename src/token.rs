@@ -9,9 +9,17 @@ pub enum Token {
     Minus,
     Star,
     Slash,
+    DoubleSlash,
     Percent,
     Hat,
 
+    // Bitwise
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+
     // Logic
     Eq,
     Neq,
@@ -96,6 +104,7 @@ fn char_to_partial_token(c: char) -> PartialToken {
         '/' => PartialToken::Slash,
         '%' => PartialToken::Percent,
         '^' => PartialToken::Hat,
+        '~' => PartialToken::Token(Token::BitXor),
 
         '(' => PartialToken::Token(Token::LBrace),
         ')' => PartialToken::Token(Token::RBrace),
@@ -128,9 +137,16 @@ impl Token {
             Token::Minus => false,
             Token::Star => false,
             Token::Slash => false,
+            Token::DoubleSlash => false,
             Token::Percent => false,
             Token::Hat => false,
 
+            Token::BitAnd => false,
+            Token::BitOr => false,
+            Token::BitXor => false,
+            Token::Shl => false,
+            Token::Shr => false,
+
             Token::Eq => false,
             Token::Neq => false,
             Token::Gt => false,
@@ -174,9 +190,16 @@ impl Token {
             Token::Minus => false,
             Token::Star => false,
             Token::Slash => false,
+            Token::DoubleSlash => false,
             Token::Percent => false,
             Token::Hat => false,
 
+            Token::BitAnd => false,
+            Token::BitOr => false,
+            Token::BitXor => false,
+            Token::Shl => false,
+            Token::Shr => false,
+
             Token::Eq => false,
             Token::Neq => false,
             Token::Gt => false,
@@ -275,16 +298,19 @@ fn parse_function<Iter: Iterator<Item = char>>(mut iter: &mut Iter) -> Result<Pa
     Ok(PartialToken::Token(Token::Function(result)))
 }
 
-/// Converts a string to a vector of partial tokens.
-fn str_to_partial_tokens(string: &str) -> Result<Vec<PartialToken>> {
+/// Converts a string to a vector of partial tokens and the byte offset where each one starts.
+fn str_to_partial_tokens(string: &str) -> Result<(Vec<PartialToken>, Vec<usize>)> {
     let mut result = Vec::new();
-    let mut iter = string.chars().peekable();
+    let mut offsets = Vec::new();
+    let mut iter = string.char_indices().peekable();
 
-    while let Some(c) = iter.next() {
+    while let Some((offset, c)) = iter.next() {
         if c == '"' {
-            result.push(parse_string_literal(&mut iter)?);
+            result.push(parse_string_literal(&mut (&mut iter).map(|(_, c)| c))?);
+            offsets.push(offset);
         } else if c == '\'' {
-            result.push(parse_function(&mut iter)?)
+            result.push(parse_function(&mut (&mut iter).map(|(_, c)| c))?);
+            offsets.push(offset);
         } else {
             let partial_token = char_to_partial_token(c);
 
@@ -300,15 +326,18 @@ fn str_to_partial_tokens(string: &str) -> Result<Vec<PartialToken>> {
 
             if !if_let_successful {
                 result.push(partial_token);
+                offsets.push(offset);
             }
         }
     }
-    Ok(result)
+    Ok((result, offsets))
 }
 
-/// Resolves all partial tokens by converting them to complex tokens.
-fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> Result<Vec<Token>> {
+/// Resolves all partial tokens by converting them to complex tokens. Each token is paired
+/// with the index of the first partial token it was made from.
+fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> Result<Vec<(Token, usize)>> {
     let mut result = Vec::new();
+    let mut index = 0;
     while !tokens.is_empty() {
         let first = tokens[0].clone();
         let second = tokens.get(1).cloned();
@@ -344,6 +373,7 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> Result<Vec<Token>> {
                 },
                 PartialToken::Slash => match second {
                     Some(PartialToken::Eq) => Some(Token::SlashAssign),
+                    Some(PartialToken::Slash) => Some(Token::DoubleSlash),
                     _ => {
                         cutoff = 1;
                         Some(Token::Slash)
@@ -367,23 +397,27 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> Result<Vec<Token>> {
                     cutoff = 1;
                     if let Ok(number) = parse_dec_or_hex(&literal) {
                         Some(Token::Int(number))
-                    } else if let Ok(number) = literal.parse::<f64>() {
+                    } else if let Some(number) = parse_float_literal(&literal) {
                         Some(Token::Float(number))
                     } else if let Ok(boolean) = literal.parse::<bool>() {
                         Some(Token::Boolean(boolean))
                     } else {
-                        // If there are two tokens following this one, check if the next one is
-                        // a plus or a minus. If so, then attempt to parse all three tokens as a
-                        // scientific notation number of the form `<coefficient>e{+,-}<exponent>`,
-                        // for example [Literal("10e"), Minus, Literal("3")] => "1e-3".parse().
-                        match (second, third) {
-                            (Some(second), Some(third))
-                                if second == PartialToken::Minus
-                                    || second == PartialToken::Plus =>
+                        // A signed exponent splits scientific notation into three partial
+                        // tokens, for example [Literal("1.5e"), Minus, Literal("7")].
+                        let sign = match second {
+                            Some(PartialToken::Minus) => Some('-'),
+                            Some(PartialToken::Plus) => Some('+'),
+                            _ => None,
+                        };
+
+                        match (sign, third) {
+                            (Some(sign), Some(PartialToken::Literal(exponent)))
+                                if is_mantissa(&literal) && is_exponent(&exponent) =>
                             {
-                                if let Ok(number) =
-                                    format!("{}{}{}", literal, second, third).parse::<f64>()
-                                {
+                                let number =
+                                    parse_float_literal(&format!("{literal}{sign}{exponent}"));
+
+                                if let Some(number) = number {
                                     cutoff = 3;
                                     Some(Token::Float(number))
                                 } else {
@@ -414,6 +448,7 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> Result<Vec<Token>> {
                 },
                 PartialToken::Gt => match second {
                     Some(PartialToken::Eq) => Some(Token::Geq),
+                    Some(PartialToken::Gt) => Some(Token::Shr),
                     _ => {
                         cutoff = 1;
                         Some(Token::Gt)
@@ -421,6 +456,7 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> Result<Vec<Token>> {
                 },
                 PartialToken::Lt => match second {
                     Some(PartialToken::Eq) => Some(Token::Leq),
+                    Some(PartialToken::Lt) => Some(Token::Shl),
                     _ => {
                         cutoff = 1;
                         Some(Token::Lt)
@@ -434,7 +470,10 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> Result<Vec<Token>> {
                         }
                         _ => Some(Token::And),
                     },
-                    _ => return Err(Error::unmatched_partial_token(first, second)),
+                    _ => {
+                        cutoff = 1;
+                        Some(Token::BitAnd)
+                    }
                 },
                 PartialToken::VerticalBar => match second {
                     Some(PartialToken::VerticalBar) => match third {
@@ -444,13 +483,17 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> Result<Vec<Token>> {
                         }
                         _ => Some(Token::Or),
                     },
-                    _ => return Err(Error::unmatched_partial_token(first, second)),
+                    _ => {
+                        cutoff = 1;
+                        Some(Token::BitOr)
+                    }
                 },
             }
-            .into_iter(),
+            .map(|token| (token, index)),
         );
 
         tokens = &tokens[cutoff..];
+        index += cutoff;
     }
     Ok(result)
 }
@@ -463,9 +506,17 @@ impl Display for Token {
             Minus => write!(f, "-"),
             Star => write!(f, "*"),
             Slash => write!(f, "/"),
+            DoubleSlash => write!(f, "//"),
             Percent => write!(f, "%"),
             Hat => write!(f, "^"),
 
+            // Bitwise
+            BitAnd => write!(f, "&"),
+            BitOr => write!(f, "|"),
+            BitXor => write!(f, "~"),
+            Shl => write!(f, "<<"),
+            Shr => write!(f, ">>"),
+
             // Logic
             Eq => write!(f, "=="),
             Neq => write!(f, "!="),
@@ -532,10 +583,28 @@ impl Display for PartialToken {
 }
 
 pub(crate) fn tokenize(string: &str) -> Result<Vec<Token>> {
-    partial_tokens_to_tokens(&str_to_partial_tokens(string)?)
+    let tokens = tokenize_with_offsets(string)?
+        .into_iter()
+        .map(|(token, _)| token)
+        .collect();
+
+    Ok(tokens)
+}
+
+/// Tokenizes the string and pairs each token with the byte offset where it starts.
+pub(crate) fn tokenize_with_offsets(string: &str) -> Result<Vec<(Token, usize)>> {
+    let (partial_tokens, offsets) = str_to_partial_tokens(string)?;
+    let tokens = partial_tokens_to_tokens(&partial_tokens)?
+        .into_iter()
+        .map(|(token, index)| (token, offsets[index]))
+        .collect();
+
+    Ok(tokens)
 }
 
 fn parse_dec_or_hex(literal: &str) -> std::result::Result<i64, std::num::ParseIntError> {
+    let literal = strip_digit_separators(literal);
+
     if let Some(literal) = literal.strip_prefix("0x") {
         literal.parse()
     } else {
@@ -543,6 +612,43 @@ fn parse_dec_or_hex(literal: &str) -> std::result::Result<i64, std::num::ParseIn
     }
 }
 
+/// Parses a float literal such as `1.5`, `1e10`, `1_000.5`, `inf` or `nan`.
+fn parse_float_literal(literal: &str) -> Option<f64> {
+    let first = literal.chars().next()?;
+
+    if first.is_ascii_digit() || first == '.' {
+        strip_digit_separators(literal).parse().ok()
+    } else {
+        match literal.to_ascii_lowercase().as_str() {
+            "inf" | "infinity" => Some(f64::INFINITY),
+            "nan" => Some(f64::NAN),
+            _ => None,
+        }
+    }
+}
+
+/// Removes the underscores used to group digits, as in `1_000`, from numeric literals.
+/// Literals that do not start with a digit are identifiers and are left untouched.
+fn strip_digit_separators(literal: &str) -> String {
+    if literal.starts_with(|c: char| c.is_ascii_digit()) {
+        literal.replace('_', "")
+    } else {
+        literal.to_string()
+    }
+}
+
+/// Whether the literal is the part of a scientific notation number before a signed
+/// exponent, for example `1.5e` in `1.5e-7`.
+fn is_mantissa(literal: &str) -> bool {
+    literal
+        .strip_suffix(|c| c == 'e' || c == 'E')
+        .is_some_and(|mantissa| parse_float_literal(mantissa).is_some())
+}
+
+fn is_exponent(literal: &str) -> bool {
+    !literal.is_empty() && literal.chars().all(|c| c.is_ascii_digit())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::token::{tokenize, Token};
@@ -559,4 +665,91 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn scientific_notation() {
+        assert_eq!(tokenize("1e10").unwrap(), [Token::Float(1e10)]);
+        assert_eq!(tokenize("1.5E-7").unwrap(), [Token::Float(1.5e-7)]);
+        assert_eq!(tokenize("2e+3").unwrap(), [Token::Float(2000.0)]);
+    }
+
+    #[test]
+    fn digit_separators() {
+        assert_eq!(tokenize("1_000.5").unwrap(), [Token::Float(1000.5)]);
+        assert_eq!(tokenize("1_000").unwrap(), [Token::Int(1000)]);
+        assert_eq!(
+            tokenize("_a").unwrap(),
+            [Token::Identifier("_a".to_string())]
+        );
+    }
+
+    #[test]
+    fn special_floats() {
+        assert_eq!(tokenize("inf").unwrap(), [Token::Float(f64::INFINITY)]);
+
+        let tokens = tokenize("nan").unwrap();
+
+        assert_eq!(1, tokens.len());
+        assert!(matches!(tokens[0], Token::Float(float) if float.is_nan()));
+    }
+
+    #[test]
+    fn subtraction_is_not_scientific_notation() {
+        assert_eq!(
+            tokenize("e-3").unwrap(),
+            [
+                Token::Identifier("e".to_string()),
+                Token::Minus,
+                Token::Int(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn floor_division() {
+        assert_eq!(
+            tokenize("7 // 2").unwrap(),
+            [Token::Int(7), Token::DoubleSlash, Token::Int(2)]
+        );
+        assert_eq!(
+            tokenize("7 / 2").unwrap(),
+            [Token::Int(7), Token::Slash, Token::Int(2)]
+        );
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        assert_eq!(
+            tokenize("6 & 3 | 1 ~ 2").unwrap(),
+            [
+                Token::Int(6),
+                Token::BitAnd,
+                Token::Int(3),
+                Token::BitOr,
+                Token::Int(1),
+                Token::BitXor,
+                Token::Int(2)
+            ]
+        );
+        assert_eq!(
+            tokenize("1 << 4 >> 2").unwrap(),
+            [
+                Token::Int(1),
+                Token::Shl,
+                Token::Int(4),
+                Token::Shr,
+                Token::Int(2)
+            ]
+        );
+        assert_eq!(
+            tokenize("a && b || c").unwrap(),
+            [
+                Token::Identifier("a".to_string()),
+                Token::And,
+                Token::Identifier("b".to_string()),
+                Token::Or,
+                Token::Identifier("c".to_string())
+            ]
+        );
+    }
 }
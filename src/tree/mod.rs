@@ -1,5 +1,5 @@
 use crate::Function;
-use crate::{token::Token, VariableMap};
+use crate::{interrupt::INTERRUPTED, token::Token, VariableMap};
 
 use crate::{
     error::{Error, Result},
@@ -9,6 +9,7 @@ use crate::{
 use std::{
     fmt::{self, Display, Formatter},
     mem,
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 // Exclude display module from coverage, as it prints not well-defined prefix notation.
@@ -320,9 +321,21 @@ impl Node {
     ///
     /// Fails, if one of the operators in the expression tree fails.
     pub fn eval_with_context(&self, context: &VariableMap) -> Result<Value> {
+        self.eval_with_context_cancellable(context, &INTERRUPTED)
+    }
+
+    fn eval_with_context_cancellable(
+        &self,
+        context: &VariableMap,
+        interrupted: &AtomicBool,
+    ) -> Result<Value> {
+        if interrupted.load(Ordering::SeqCst) {
+            return Err(Error::Interrupted);
+        }
+
         let mut arguments = Vec::new();
         for child in self.children() {
-            arguments.push(child.eval_with_context(context)?);
+            arguments.push(child.eval_with_context_cancellable(context, interrupted)?);
         }
         self.operator().eval(&arguments, context)
     }
@@ -331,9 +344,23 @@ impl Node {
     ///
     /// Fails, if one of the operators in the expression tree fails.
     pub fn eval_with_context_mut(&self, context: &mut VariableMap) -> Result<Value> {
+        self.eval_with_context_mut_cancellable(context, &INTERRUPTED)
+    }
+
+    /// Evaluates the tree like `eval_with_context_mut`, but stops with `Error::Interrupted`
+    /// as soon as the given flag is set. The flag is checked before every node.
+    fn eval_with_context_mut_cancellable(
+        &self,
+        context: &mut VariableMap,
+        interrupted: &AtomicBool,
+    ) -> Result<Value> {
+        if interrupted.load(Ordering::SeqCst) {
+            return Err(Error::Interrupted);
+        }
+
         let mut arguments = Vec::new();
         for child in self.children() {
-            arguments.push(child.eval_with_context_mut(context)?);
+            arguments.push(child.eval_with_context_mut_cancellable(context, interrupted)?);
         }
         self.operator().eval_mut(&arguments, context)
     }
@@ -589,7 +616,13 @@ impl Node {
         }
     }
 
-    fn insert_back_prioritized(&mut self, node: Node, is_root_node: bool) -> Result<()> {
+    fn insert_back_prioritized(
+        &mut self,
+        node: Node,
+        is_root_node: bool,
+        token: &Token,
+        position: usize,
+    ) -> Result<()> {
         // println!(
         //     "Inserting {:?} into {:?}, is_root_node = {is_root_node}",
         //     node.operator(),
@@ -620,7 +653,7 @@ impl Node {
                     self.children
                         .last_mut()
                         .unwrap()
-                        .insert_back_prioritized(node, false)
+                        .insert_back_prioritized(node, false, token, position)
                 } else {
                     // println!("Rotating");
                     if node.operator().is_leaf() {
@@ -632,14 +665,14 @@ impl Node {
                     // Root nodes have at most one child
                     // TODO I am not sure if this is the correct error
                     if self.operator() == &Operator::RootNode && !self.children().is_empty() {
-                        return Err(Error::MissingOperatorOutsideOfBrace);
+                        return Err(Error::missing_operator_outside_of_brace(token, position));
                     }
                     // Do not insert root nodes into root nodes.
                     // TODO I am not sure if this is the correct error
                     if self.operator() == &Operator::RootNode
                         && node.operator() == &Operator::RootNode
                     {
-                        return Err(Error::MissingOperatorOutsideOfBrace);
+                        return Err(Error::missing_operator_outside_of_brace(token, position));
                     }
                     self.children.push(node);
                     let node = self.children.last_mut().unwrap();
@@ -647,14 +680,14 @@ impl Node {
                     // Root nodes have at most one child
                     // TODO I am not sure if this is the correct error
                     if node.operator() == &Operator::RootNode && !node.children().is_empty() {
-                        return Err(Error::MissingOperatorOutsideOfBrace);
+                        return Err(Error::missing_operator_outside_of_brace(token, position));
                     }
                     // Do not insert root nodes into root nodes.
                     // TODO I am not sure if this is the correct error
                     if node.operator() == &Operator::RootNode
                         && last_child.operator() == &Operator::RootNode
                     {
-                        return Err(Error::MissingOperatorOutsideOfBrace);
+                        return Err(Error::missing_operator_outside_of_brace(token, position));
                     }
                     node.children.push(last_child);
                     Ok(())
@@ -672,6 +705,9 @@ impl Node {
 
 impl Display for Node {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.operator == Operator::ConstAssign {
+            write!(f, "const")?;
+        }
         self.operator.fmt(f)?;
         for child in self.children() {
             write!(f, " {}", child)?;
@@ -705,7 +741,11 @@ fn collapse_root_stack_to(
     Ok(root)
 }
 
-fn collapse_all_sequences(root_stack: &mut Vec<Node>) -> Result<()> {
+fn collapse_all_sequences(
+    root_stack: &mut Vec<Node>,
+    token: &Token,
+    position: usize,
+) -> Result<()> {
     // println!("Collapsing all sequences");
     // println!("Initial root stack is: {:?}", root_stack);
     let mut root = if let Some(root) = root_stack.pop() {
@@ -719,7 +759,7 @@ fn collapse_all_sequences(root_stack: &mut Vec<Node>) -> Result<()> {
         if root.operator() == &Operator::RootNode {
             // This should fire if parsing something like `4(5)`
             if root.has_too_many_children() {
-                return Err(Error::MissingOperatorOutsideOfBrace);
+                return Err(Error::missing_operator_outside_of_brace(token, position));
             }
 
             root_stack.push(root);
@@ -728,12 +768,23 @@ fn collapse_all_sequences(root_stack: &mut Vec<Node>) -> Result<()> {
 
         if let Some(mut potential_higher_root) = root_stack.pop() {
             if root.operator().is_sequence() {
+                // A trailing comma does not start a new element, so `(a,)` has a single element.
+                if root.operator() == &Operator::Tuple && root.children.len() > 1 {
+                    let last_child_is_empty = root.children.last().is_some_and(|last| {
+                        last.operator() == &Operator::RootNode && last.children().is_empty()
+                    });
+
+                    if last_child_is_empty {
+                        root.children.pop();
+                    }
+                }
+
                 potential_higher_root.children.push(root);
                 root = potential_higher_root;
             } else {
                 // This should fire if parsing something like `4(5)`
                 if root.has_too_many_children() {
-                    return Err(Error::MissingOperatorOutsideOfBrace);
+                    return Err(Error::missing_operator_outside_of_brace(token, position));
                 }
 
                 root_stack.push(potential_higher_root);
@@ -752,11 +803,41 @@ fn collapse_all_sequences(root_stack: &mut Vec<Node>) -> Result<()> {
 
 pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node> {
     let mut root_stack = vec![Node::root_node()];
+    let mut last_token: Option<Token> = None;
     let mut last_token_is_rightsided_value = false;
-    let mut token_iter = tokens.iter().peekable();
+    let mut const_assignment_pending = false;
+    let mut token_iter = tokens.iter().enumerate().peekable();
+
+    while let Some((position, token)) = token_iter.next() {
+        let token = token.clone();
+        let next = token_iter.peek().map(|(_, next)| *next);
+
+        // A value other than a function identifier cannot be followed by an opening brace and
+        // a closing brace cannot be followed by a value, e.g. `4(5)` or `(5)4`.
+        match &last_token {
+            Some(Token::RBrace) if token.is_leftsided_value() => {
+                return Err(Error::missing_operator_outside_of_brace(&token, position));
+            }
+            Some(last)
+                if token == Token::LBrace
+                    && last.is_rightsided_value()
+                    && !matches!(last, Token::Identifier(_)) =>
+            {
+                return Err(Error::missing_operator_outside_of_brace(&token, position));
+            }
+            _ => {}
+        }
 
-    while let Some(token) = token_iter.next().cloned() {
-        let next = token_iter.peek().cloned();
+        // `const` is only a keyword when it starts an assignment, as in `const x = 1`.
+        if let (Token::Identifier(identifier), Some(Token::Identifier(_)), Some(Token::Assign)) =
+            (&token, next, tokens.get(position + 2))
+        {
+            if identifier == "const" {
+                const_assignment_pending = true;
+
+                continue;
+            }
+        }
 
         let node = match token.clone() {
             Token::Plus => Some(Node::new(Operator::Add)),
@@ -769,9 +850,16 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node> {
             }
             Token::Star => Some(Node::new(Operator::Mul)),
             Token::Slash => Some(Node::new(Operator::Div)),
+            Token::DoubleSlash => Some(Node::new(Operator::FloorDiv)),
             Token::Percent => Some(Node::new(Operator::Mod)),
             Token::Hat => Some(Node::new(Operator::Exp)),
 
+            Token::BitAnd => Some(Node::new(Operator::BitAnd)),
+            Token::BitOr => Some(Node::new(Operator::BitOr)),
+            Token::BitXor => Some(Node::new(Operator::BitXor)),
+            Token::Shl => Some(Node::new(Operator::Shl)),
+            Token::Shr => Some(Node::new(Operator::Shr)),
+
             Token::Eq => Some(Node::new(Operator::Eq)),
             Token::Neq => Some(Node::new(Operator::Neq)),
             Token::Gt => Some(Node::new(Operator::Gt)),
@@ -790,12 +878,20 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node> {
                 if root_stack.len() <= 1 {
                     return Err(Error::UnmatchedRBrace);
                 } else {
-                    collapse_all_sequences(&mut root_stack)?;
+                    collapse_all_sequences(&mut root_stack, &token, position)?;
                     root_stack.pop()
                 }
             }
 
-            Token::Assign => Some(Node::new(Operator::Assign)),
+            Token::Assign => {
+                if const_assignment_pending {
+                    const_assignment_pending = false;
+
+                    Some(Node::new(Operator::ConstAssign))
+                } else {
+                    Some(Node::new(Operator::Assign))
+                }
+            }
             Token::PlusAssign => Some(Node::new(Operator::AddAssign)),
             Token::MinusAssign => Some(Node::new(Operator::SubAssign)),
             Token::StarAssign => Some(Node::new(Operator::MulAssign)),
@@ -873,7 +969,7 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node> {
                 // println!("Stack after sequence operation: {:?}", root_stack);
                 } else if root.operator().is_sequence() {
                     if let Some(mut last_root_child) = root.children.pop() {
-                        last_root_child.insert_back_prioritized(node, true)?;
+                        last_root_child.insert_back_prioritized(node, true, &token, position)?;
                         root.children.push(last_root_child);
                         root_stack.push(root);
                     } else {
@@ -881,7 +977,7 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node> {
                         unreachable!()
                     }
                 } else {
-                    root.insert_back_prioritized(node, true)?;
+                    root.insert_back_prioritized(node, true, &token, position)?;
                     root_stack.push(root);
                 }
             } else {
@@ -890,10 +986,13 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node> {
         }
 
         last_token_is_rightsided_value = token.is_rightsided_value();
+        last_token = Some(token);
     }
 
     // In the end, all sequences are implicitly terminated
-    collapse_all_sequences(&mut root_stack)?;
+    if let Some(token) = &last_token {
+        collapse_all_sequences(&mut root_stack, token, tokens.len() - 1)?;
+    }
 
     if root_stack.len() > 1 {
         Err(Error::UnmatchedLBrace)
@@ -903,3 +1002,172 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node> {
         Err(Error::UnmatchedRBrace)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{eval, eval_with_context, token::tokenize, Error, Table};
+
+    use super::*;
+
+    #[test]
+    fn missing_operator_before_brace() {
+        let error = tokens_to_operator_tree(tokenize("4(5)").unwrap()).unwrap_err();
+
+        assert_eq!(
+            Error::MissingOperatorOutsideOfBrace {
+                token: "(".to_string(),
+                position: 1,
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn missing_operator_after_brace() {
+        let error = tokens_to_operator_tree(tokenize("(5)4").unwrap()).unwrap_err();
+
+        assert_eq!(
+            Error::MissingOperatorOutsideOfBrace {
+                token: "4".to_string(),
+                position: 3,
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn call_with_parentheses_and_juxtaposition() {
+        let parenthesized = tokens_to_operator_tree(tokenize("count(list)").unwrap()).unwrap();
+        let juxtaposed = tokens_to_operator_tree(tokenize("count list").unwrap()).unwrap();
+
+        assert_eq!(
+            &Operator::function_identifier("count".to_string()),
+            parenthesized.children()[0].operator()
+        );
+        assert_eq!(
+            juxtaposed.children()[0].operator(),
+            parenthesized.children()[0].operator()
+        );
+        assert_eq!(Ok(Value::Integer(3)), eval("list = (1, 2, 3); count(list)"));
+        assert_eq!(
+            eval("list = (1, 2, 3); count(list)"),
+            eval("list = (1, 2, 3); count list")
+        );
+    }
+
+    #[test]
+    fn call_with_argument_list() {
+        let mut table = Table::new(vec!["foo".to_string(), "bar".to_string()]);
+
+        table
+            .insert(vec![Value::Integer(0), Value::Integer(0)])
+            .unwrap();
+        table
+            .insert(vec![Value::Integer(0), Value::Integer(0)])
+            .unwrap();
+        table
+            .insert(vec![Value::Integer(1), Value::Integer(2)])
+            .unwrap();
+
+        let source = "table = create_table((\"foo\", \"bar\"), ((0, 0), (0, 0)));";
+        let parenthesized = eval(&format!("{source} insert(table, (1, 2))"));
+        let method = eval(&format!("{source} table:insert((1, 2))"));
+
+        assert_eq!(Ok(Value::Table(table)), parenthesized);
+        assert_eq!(parenthesized, method);
+    }
+
+    #[test]
+    fn parenthesized_value_and_trailing_comma() {
+        assert_eq!(Ok(Value::Integer(1)), eval("(1)"));
+        assert_eq!(Ok(Value::List(vec![Value::Integer(1)])), eval("(1,)"));
+        assert_eq!(
+            Ok(Value::List(vec![Value::Integer(1), Value::Integer(2)])),
+            eval("(1, 2)")
+        );
+        assert_eq!(eval("(1, 2)"), eval("(1, 2,)"));
+    }
+
+    #[test]
+    fn negative_numbers() {
+        assert_eq!(Ok(Value::Integer(-3)), eval("-3"));
+        assert_eq!(Ok(Value::Integer(8)), eval("5 - -3"));
+        assert_eq!(Ok(Value::Integer(8)), eval("5--3"));
+        assert_eq!(
+            Ok(Value::List(vec![Value::Integer(-1), Value::Integer(-2)])),
+            eval("(-1, -2)")
+        );
+        assert_eq!(Ok(Value::Float(0.5)), eval("2 ^ -1"));
+        assert_eq!(Ok(Value::Integer(-6)), eval("2 * -3"));
+        assert_eq!(Ok(Value::Integer(-3)), eval("-(1 + 2)"));
+    }
+
+    #[test]
+    fn const_assignment() {
+        let mut context = VariableMap::new();
+
+        eval_with_context("const x = 1", &mut context).unwrap();
+
+        assert_eq!(
+            Ok(Value::Integer(2)),
+            eval_with_context("x + 1", &mut context)
+        );
+        assert!(eval_with_context("x = 2", &mut context).is_err());
+        assert!(eval_with_context("x += 2", &mut context).is_err());
+        assert!(eval_with_context("const x = 2", &mut context).is_err());
+        assert_eq!(Ok(Value::Integer(1)), eval_with_context("x", &mut context));
+    }
+
+    #[test]
+    fn compound_assignment_on_collection_items() {
+        assert_eq!(
+            Ok(Value::Integer(3)),
+            eval("map.count = 1; map.count += 2; map.count")
+        );
+        assert_eq!(
+            Ok(Value::List(vec![Value::Integer(2), Value::Integer(4)])),
+            eval("list = (1, 2); list.0 += 1; list.1 *= 2; list")
+        );
+    }
+
+    #[test]
+    fn interrupted_evaluation() {
+        let tree = tokens_to_operator_tree(tokenize("x = 1; wait(200); y = 2").unwrap()).unwrap();
+        let interrupted = AtomicBool::new(false);
+        let mut context = VariableMap::new();
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                interrupted.store(true, Ordering::SeqCst);
+            });
+
+            assert_eq!(
+                tree.eval_with_context_mut_cancellable(&mut context, &interrupted),
+                Err(Error::Interrupted)
+            );
+        });
+
+        assert_eq!(context.get_value("x").unwrap(), Some(Value::Integer(1)));
+        assert_eq!(context.get_value("y").unwrap(), None);
+    }
+
+    #[test]
+    fn const_assign_display() {
+        let children = vec![
+            Node::new(Operator::value(Value::String("x".to_string()))),
+            Node::new(Operator::value(Value::Integer(1))),
+        ];
+        let assign = Node {
+            operator: Operator::Assign,
+            children: children.clone(),
+        };
+        let const_assign = Node {
+            operator: Operator::ConstAssign,
+            children,
+        };
+
+        assert_eq!(" = ", Operator::ConstAssign.to_string());
+        assert_eq!(format!("const{assign}"), const_assign.to_string());
+    }
+}
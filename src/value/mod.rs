@@ -135,6 +135,14 @@ impl Value {
         }
     }
 
+    /// Mutably borrows the value stored in `self` as `Vec<Value>`, or returns `Err` if `self` is not a `Value::List`.
+    pub fn as_list_mut(&mut self) -> Result<&mut Vec<Value>> {
+        match self {
+            Value::List(list) => Ok(list),
+            value => Err(Error::expected_list(value.clone())),
+        }
+    }
+
     /// Borrows the value stored in `self` as `Vec<Value>`, or returns `Err` if `self` is not a `Value::List`.
     pub fn into_inner_list(self) -> Result<Vec<Value>> {
         match self {
@@ -264,7 +272,7 @@ impl Serialize for Value {
 
                 tuple.end()
             }
-            Value::Empty => todo!(),
+            Value::Empty => serializer.serialize_unit(),
             Value::Map(inner) => inner.serialize(serializer),
             Value::Table(inner) => inner.serialize(serializer),
             Value::Function(inner) => inner.serialize(serializer),
@@ -633,31 +641,21 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: serde::de::Error,
     {
-        Err(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &self,
-        ))
+        Ok(Value::Empty)
     }
 
     fn visit_some<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let _ = deserializer;
-        Err(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Option,
-            &self,
-        ))
+        deserializer.deserialize_any(self)
     }
 
     fn visit_unit<E>(self) -> std::result::Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
-        Err(serde::de::Error::invalid_type(
-            serde::de::Unexpected::Unit,
-            &self,
-        ))
+        Ok(Value::Empty)
     }
 
     fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
@@ -725,3 +723,44 @@ impl<'de> Deserialize<'de> for Value {
         deserializer.deserialize_any(ValueVisitor::new())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_empty_as_null() {
+        let mut map = VariableMap::new();
+
+        map.set_value("a", Value::Empty).unwrap();
+        map.set_value("b", Value::Integer(1)).unwrap();
+
+        assert_eq!(
+            r#"{"a":null,"b":1}"#,
+            serde_json::to_string(&Value::Map(map)).unwrap()
+        );
+    }
+
+    #[test]
+    fn deserialize_null_as_empty() {
+        let value: Value = serde_json::from_str(r#"{"a": null}"#).unwrap();
+        let mut map = VariableMap::new();
+
+        map.set_value("a", Value::Empty).unwrap();
+
+        assert_eq!(Value::Map(map), value);
+    }
+
+    #[test]
+    fn push_to_list_in_place() {
+        let mut value = Value::List(vec![Value::Integer(1)]);
+
+        value.as_list_mut().unwrap().push(Value::Integer(2));
+
+        assert_eq!(
+            Value::List(vec![Value::Integer(1), Value::Integer(2)]),
+            value
+        );
+        assert!(Value::Integer(1).as_list_mut().is_err());
+    }
+}
@@ -1,12 +1,82 @@
 use crate::{Error, Result, Value, VariableMap};
 use clap::ValueEnum;
-use comfy_table::{Cell, Color, ContentArrangement, Table as ComfyTable};
+use comfy_table::{
+    presets::{ASCII_FULL, ASCII_MARKDOWN, NOTHING},
+    Cell, CellAlignment, Color, ContentArrangement, Table as ComfyTable,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Display, Formatter},
 };
 
+/// Border presets for rendering a table as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableStyle {
+    Ascii,
+    #[default]
+    Rounded,
+    Markdown,
+    None,
+}
+
+impl TableStyle {
+    /// Looks up a style by its lowercase name, e.g. "markdown".
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "ascii" => Ok(TableStyle::Ascii),
+            "rounded" => Ok(TableStyle::Rounded),
+            "markdown" => Ok(TableStyle::Markdown),
+            "none" => Ok(TableStyle::None),
+            _ => Err(Error::CustomMessage(format!(
+                "Unknown table style {name}. Use ascii, rounded, markdown or none."
+            ))),
+        }
+    }
+
+    fn preset(&self) -> &'static str {
+        match self {
+            TableStyle::Ascii => ASCII_FULL,
+            TableStyle::Rounded => "││──├─┼┤│    ┬┴╭╮╰╯",
+            TableStyle::Markdown => ASCII_MARKDOWN,
+            TableStyle::None => NOTHING,
+        }
+    }
+}
+
+/// Horizontal alignment of the cells in a rendered column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnAlignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl ColumnAlignment {
+    /// Looks up an alignment by its lowercase name, e.g. "right".
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "left" => Ok(ColumnAlignment::Left),
+            "center" => Ok(ColumnAlignment::Center),
+            "right" => Ok(ColumnAlignment::Right),
+            _ => Err(Error::CustomMessage(format!(
+                "Unknown alignment {name}. Use left, center or right."
+            ))),
+        }
+    }
+}
+
+impl From<ColumnAlignment> for CellAlignment {
+    fn from(alignment: ColumnAlignment) -> Self {
+        match alignment {
+            ColumnAlignment::Left => CellAlignment::Left,
+            ColumnAlignment::Center => CellAlignment::Center,
+            ColumnAlignment::Right => CellAlignment::Right,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Table {
     column_names: Vec<String>,
@@ -58,6 +128,29 @@ impl Table {
         Ok(())
     }
 
+    pub fn insert_column(&mut self, name: String, values: Vec<Value>) -> Result<()> {
+        if values.len() != self.rows.len() {
+            return Err(Error::WrongRowAmount {
+                expected: self.rows.len(),
+                actual: values.len(),
+            });
+        }
+
+        if self.column_names.contains(&name) {
+            return Err(Error::CustomMessage(format!(
+                "This table already has a column named {name}."
+            )));
+        }
+
+        self.column_names.push(name);
+
+        for (row, value) in self.rows.iter_mut().zip(values) {
+            row.push(value);
+        }
+
+        Ok(())
+    }
+
     pub fn remove(&mut self, index: usize) -> Result<()> {
         self.rows.remove(index);
 
@@ -133,6 +226,148 @@ impl Table {
         Some(filtered)
     }
 
+    /// Returns a copy with one row for each item of the list in the given column, like
+    /// SQL's `UNNEST`. Rows whose list is empty are dropped, or kept with an empty cell if
+    /// `keep_empty` is true. Cells that are not lists are left as they are.
+    pub fn explode(&self, column_name: &str, keep_empty: bool) -> Result<Table> {
+        let column_index = self.column_index_or_err(column_name)?;
+        let mut exploded = Table::new(self.column_names.clone());
+
+        for row in &self.rows {
+            match &row[column_index] {
+                Value::List(items) if items.is_empty() => {
+                    if keep_empty {
+                        let mut new_row = row.clone();
+
+                        new_row[column_index] = Value::Empty;
+                        exploded.rows.push(new_row);
+                    }
+                }
+                Value::List(items) => {
+                    for item in items {
+                        let mut new_row = row.clone();
+
+                        new_row[column_index] = item.clone();
+                        exploded.rows.push(new_row);
+                    }
+                }
+                _ => exploded.rows.push(row.clone()),
+            }
+        }
+
+        Ok(exploded)
+    }
+
+    /// Replaces every cell in a column with the result of calling `transform` on it.
+    pub fn map_column(
+        &mut self,
+        column_name: &str,
+        mut transform: impl FnMut(&Value) -> Result<Value>,
+    ) -> Result<()> {
+        let column_index = self.column_index_or_err(column_name)?;
+
+        for row in &mut self.rows {
+            row[column_index] = transform(&row[column_index])?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces every empty cell with a copy of `value`.
+    pub fn fill_empty(&mut self, value: &Value) {
+        for cell in self.rows.iter_mut().flatten() {
+            if cell.is_empty() {
+                *cell = value.clone();
+            }
+        }
+    }
+
+    /// Replaces every empty cell with the last non-empty value above it in the same column.
+    /// Empty cells at the top of a column stay empty.
+    pub fn fill_forward(&mut self) {
+        let mut previous = vec![Value::Empty; self.column_names.len()];
+
+        for row in &mut self.rows {
+            for (cell, previous) in row.iter_mut().zip(previous.iter_mut()) {
+                if cell.is_empty() {
+                    *cell = previous.clone();
+                } else {
+                    *previous = cell.clone();
+                }
+            }
+        }
+    }
+
+    /// Adds a column that holds, for each row, the first non-empty value among the source
+    /// columns. Rows where every source is empty get an empty cell.
+    pub fn coalesce_columns(&mut self, name: String, sources: &[String]) -> Result<()> {
+        let source_indexes = sources
+            .iter()
+            .map(|column_name| self.column_index_or_err(column_name))
+            .collect::<Result<Vec<usize>>>()?;
+        let values = self
+            .rows
+            .iter()
+            .map(|row| {
+                source_indexes
+                    .iter()
+                    .map(|index| &row[*index])
+                    .find(|value| !value.is_empty())
+                    .cloned()
+                    .unwrap_or(Value::Empty)
+            })
+            .collect();
+
+        self.insert_column(name, values)
+    }
+
+    /// Counts how often each pair of values from two columns occurs together. The result has
+    /// one row for each value of `row_column` and one count column for each value of
+    /// `column_column`, both in sorted order.
+    pub fn crosstab(&self, row_column: &str, column_column: &str) -> Result<Table> {
+        let row_index = self.column_index_or_err(row_column)?;
+        let column_index = self.column_index_or_err(column_column)?;
+        let column_values: BTreeSet<&Value> =
+            self.rows.iter().map(|row| &row[column_index]).collect();
+        let mut counts: BTreeMap<&Value, Vec<i64>> = BTreeMap::new();
+
+        for row in &self.rows {
+            let position = column_values
+                .iter()
+                .position(|value| *value == &row[column_index])
+                .unwrap();
+
+            counts
+                .entry(&row[row_index])
+                .or_insert_with(|| vec![0; column_values.len()])[position] += 1;
+        }
+
+        let mut column_names = vec![row_column.to_string()];
+
+        for value in &column_values {
+            let name = value.to_string();
+
+            if column_names.contains(&name) {
+                return Err(Error::CustomMessage(format!(
+                    "Cannot crosstab {column_column} because more than one column would be named {name}."
+                )));
+            }
+
+            column_names.push(name);
+        }
+
+        let mut crosstab = Table::new(column_names);
+
+        for (value, row_counts) in counts {
+            let mut row = vec![value.clone()];
+
+            row.extend(row_counts.into_iter().map(Value::Integer));
+            crosstab.rows.push(row);
+        }
+
+        Ok(crosstab)
+    }
+
     pub fn get_column_index(&self, column_name: &str) -> Option<usize> {
         let column_names = &self.column_names;
         for (i, column) in column_names.iter().enumerate() {
@@ -142,14 +377,33 @@ impl Table {
         }
         None
     }
+
+    /// Like [Table::get_column_index], but fails with a helpful message if the column is
+    /// missing.
+    pub fn column_index_or_err(&self, column_name: &str) -> Result<usize> {
+        self.get_column_index(column_name).ok_or_else(|| {
+            Error::CustomMessage(format!("This table has no column named {column_name}."))
+        })
+    }
 }
 
-impl Display for Table {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+impl Table {
+    /// Draws the table as text with the given border style.
+    pub fn render(&self, style: TableStyle) -> String {
+        self.render_aligned(style, &BTreeMap::new())
+    }
+
+    /// Draws the table as text with the given border style. Columns that are not named in
+    /// `alignments` are right-aligned if they only hold numbers and left-aligned otherwise.
+    pub fn render_aligned(
+        &self,
+        style: TableStyle,
+        alignments: &BTreeMap<String, ColumnAlignment>,
+    ) -> String {
         let mut table = ComfyTable::new();
 
         table
-            .load_preset("││──├─┼┤│    ┬┴╭╮╰╯")
+            .load_preset(style.preset())
             .set_content_arrangement(ContentArrangement::Dynamic)
             .set_header(&self.column_names);
 
@@ -193,7 +447,41 @@ impl Display for Table {
             table.set_header(["empty"]);
         }
 
-        write!(f, "{table}")
+        for (index, name) in self.column_names.iter().enumerate() {
+            let alignment = alignments
+                .get(name)
+                .copied()
+                .unwrap_or_else(|| self.inferred_alignment(index));
+
+            if let Some(column) = table.column_mut(index) {
+                column.set_cell_alignment(alignment.into());
+            }
+        }
+
+        table.to_string()
+    }
+
+    fn inferred_alignment(&self, column_index: usize) -> ColumnAlignment {
+        let mut values = self
+            .rows
+            .iter()
+            .filter_map(|row| row.get(column_index))
+            .filter(|value| !value.is_empty())
+            .peekable();
+        let is_numeric = values.peek().is_some()
+            && values.all(|value| matches!(value, Value::Integer(_) | Value::Float(_)));
+
+        if is_numeric {
+            ColumnAlignment::Right
+        } else {
+            ColumnAlignment::Left
+        }
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(TableStyle::default()))
     }
 }
 
@@ -326,11 +614,7 @@ impl Eq for Table {}
 
 impl PartialEq for Table {
     fn eq(&self, other: &Self) -> bool {
-        if self.column_names != other.column_names {
-            return false;
-        }
-
-        self.rows == other.rows
+        self.column_names == other.column_names && self.rows == other.rows
     }
 }
 
@@ -345,3 +629,306 @@ impl Ord for Table {
         self.column_names.cmp(&other.column_names)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_table(rows: &[(i64, &str)]) -> Table {
+        let mut table = Table::new(vec!["id".to_string(), "name".to_string()]);
+
+        for (id, name) in rows {
+            table
+                .insert(vec![Value::Integer(*id), Value::String(name.to_string())])
+                .unwrap();
+        }
+
+        table
+    }
+
+    #[test]
+    fn equal_tables_with_multiple_rows() {
+        let left = create_table(&[(1, "foo"), (2, "bar"), (3, "baz")]);
+        let right = create_table(&[(1, "foo"), (2, "bar"), (3, "baz")]);
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn insert_column() {
+        let mut table = create_table(&[(1, "foo"), (2, "bar")]);
+
+        table
+            .insert_column(
+                "active".to_string(),
+                vec![Value::Boolean(true), Value::Boolean(false)],
+            )
+            .unwrap();
+
+        assert_eq!(
+            &vec!["id".to_string(), "name".to_string(), "active".to_string()],
+            table.column_names()
+        );
+        assert_eq!(
+            Some(&vec![
+                Value::Integer(2),
+                Value::String("bar".to_string()),
+                Value::Boolean(false)
+            ]),
+            table.get(1)
+        );
+    }
+
+    #[test]
+    fn insert_column_wrong_length() {
+        let mut table = create_table(&[(1, "foo"), (2, "bar")]);
+        let error = table
+            .insert_column("active".to_string(), vec![Value::Boolean(true)])
+            .unwrap_err();
+
+        assert_eq!(
+            Error::WrongRowAmount {
+                expected: 2,
+                actual: 1
+            },
+            error
+        );
+        assert_eq!(2, table.column_names().len());
+    }
+
+    #[test]
+    fn insert_duplicate_column() {
+        let mut table = create_table(&[(1, "foo")]);
+
+        assert!(table
+            .insert_column("name".to_string(), vec![Value::Empty])
+            .is_err());
+        assert_eq!(2, table.column_names().len());
+    }
+
+    #[test]
+    fn missing_column_index() {
+        let table = create_table(&[(1, "foo")]);
+
+        assert_eq!(Ok(1), table.column_index_or_err("name"));
+        assert_eq!(
+            Err(Error::CustomMessage(
+                "This table has no column named age.".to_string()
+            )),
+            table.column_index_or_err("age")
+        );
+    }
+
+    #[test]
+    fn differently_ordered_tables() {
+        let left = create_table(&[(1, "foo"), (2, "bar")]);
+        let right = create_table(&[(2, "bar"), (1, "foo")]);
+
+        assert_ne!(left, right);
+    }
+
+    #[test]
+    fn render_styles() {
+        let table = create_table(&[(1, "foo")]);
+        let ascii = table.render(TableStyle::Ascii);
+        let rounded = table.render(TableStyle::Rounded);
+
+        assert!(ascii.starts_with('+'));
+        assert!(!ascii.contains('╭'));
+        assert!(rounded.starts_with('╭'));
+        assert!(!rounded.contains('+'));
+        assert_eq!(rounded, table.to_string());
+    }
+
+    #[test]
+    fn render_alignment() {
+        let mut table = Table::new(vec!["count".to_string(), "name".to_string()]);
+
+        table
+            .insert(vec![Value::Integer(1), Value::String("a".to_string())])
+            .unwrap();
+        table
+            .insert(vec![Value::Integer(100), Value::String("abc".to_string())])
+            .unwrap();
+
+        let inferred = table.render(TableStyle::Ascii);
+
+        assert!(inferred.contains("|     1 | a    |"));
+
+        let explicit = table.render_aligned(
+            TableStyle::Ascii,
+            &BTreeMap::from([
+                ("count".to_string(), ColumnAlignment::Left),
+                ("name".to_string(), ColumnAlignment::Right),
+            ]),
+        );
+
+        assert!(explicit.contains("| 1     |    a |"));
+    }
+
+    #[test]
+    fn crosstab() {
+        let mut table = Table::new(vec!["region".to_string(), "product".to_string()]);
+
+        for (region, product) in [
+            ("north", "tea"),
+            ("south", "coffee"),
+            ("north", "coffee"),
+            ("north", "tea"),
+            ("south", "coffee"),
+        ] {
+            table
+                .insert(vec![
+                    Value::String(region.to_string()),
+                    Value::String(product.to_string()),
+                ])
+                .unwrap();
+        }
+
+        let crosstab = table.crosstab("region", "product").unwrap();
+
+        assert_eq!(
+            &vec![
+                "region".to_string(),
+                "coffee".to_string(),
+                "tea".to_string()
+            ],
+            crosstab.column_names()
+        );
+        assert_eq!(
+            &vec![
+                vec![
+                    Value::String("north".to_string()),
+                    Value::Integer(1),
+                    Value::Integer(2)
+                ],
+                vec![
+                    Value::String("south".to_string()),
+                    Value::Integer(2),
+                    Value::Integer(0)
+                ],
+            ],
+            crosstab.rows()
+        );
+        assert!(table.crosstab("region", "price").is_err());
+    }
+
+    #[test]
+    fn crosstab_duplicate_headers() {
+        let mut table = Table::new(vec!["key".to_string(), "value".to_string()]);
+
+        for value in [
+            Value::Integer(1),
+            Value::String("1".to_string()),
+            Value::String("key".to_string()),
+        ] {
+            table
+                .insert(vec![Value::String("a".to_string()), value])
+                .unwrap();
+        }
+
+        assert!(matches!(
+            table.crosstab("key", "value"),
+            Err(Error::CustomMessage(_))
+        ));
+
+        table.remove(0).unwrap();
+
+        assert!(matches!(
+            table.crosstab("key", "value"),
+            Err(Error::CustomMessage(_))
+        ));
+    }
+
+    #[test]
+    fn fill_empty_cells() {
+        let mut table = Table::new(vec!["id".to_string(), "score".to_string()]);
+
+        for (id, score) in [
+            (1, Value::Integer(10)),
+            (2, Value::Empty),
+            (3, Value::Empty),
+            (4, Value::Integer(40)),
+        ] {
+            table.insert(vec![Value::Integer(id), score]).unwrap();
+        }
+
+        let mut filled = table.clone();
+        let mut forward = table;
+
+        filled.fill_empty(&Value::Integer(0));
+        forward.fill_forward();
+
+        let scores = |table: &Table| -> Vec<Value> {
+            table.rows().iter().map(|row| row[1].clone()).collect()
+        };
+
+        assert_eq!(
+            vec![
+                Value::Integer(10),
+                Value::Integer(0),
+                Value::Integer(0),
+                Value::Integer(40)
+            ],
+            scores(&filled)
+        );
+        assert_eq!(
+            vec![
+                Value::Integer(10),
+                Value::Integer(10),
+                Value::Integer(10),
+                Value::Integer(40)
+            ],
+            scores(&forward)
+        );
+    }
+
+    #[test]
+    fn coalesce_complementary_columns() {
+        let mut table = Table::new(vec!["email".to_string(), "contact".to_string()]);
+
+        table
+            .insert(vec![
+                Value::String("a@example.com".to_string()),
+                Value::Empty,
+            ])
+            .unwrap();
+        table
+            .insert(vec![
+                Value::Empty,
+                Value::String("b@example.com".to_string()),
+            ])
+            .unwrap();
+        table
+            .insert(vec![
+                Value::String("c@example.com".to_string()),
+                Value::String("other@example.com".to_string()),
+            ])
+            .unwrap();
+
+        table
+            .coalesce_columns(
+                "address".to_string(),
+                &["email".to_string(), "contact".to_string()],
+            )
+            .unwrap();
+
+        let addresses: Vec<&Value> = table.rows().iter().map(|row| &row[2]).collect();
+
+        assert_eq!(
+            vec![
+                &Value::String("a@example.com".to_string()),
+                &Value::String("b@example.com".to_string()),
+                &Value::String("c@example.com".to_string()),
+            ],
+            addresses
+        );
+        assert!(table
+            .coalesce_columns("missing".to_string(), &["phone".to_string()])
+            .is_err());
+        assert!(table
+            .coalesce_columns("email".to_string(), &["contact".to_string()])
+            .is_err());
+        assert_eq!(3, table.column_names().len());
+    }
+}
@@ -1,9 +1,9 @@
 use std::{
     fmt::{self, Display, Formatter},
-    time::{Instant, SystemTime, UNIX_EPOCH},
+    time::{Duration as StdDuration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use chrono::{DateTime, FixedOffset, Local as LocalTime, NaiveDateTime};
+use chrono::{DateTime, Duration, FixedOffset, Local as LocalTime, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -27,6 +27,11 @@ impl Time {
         Time::Utc(utc)
     }
 
+    /// Create a UTC time from a date and time that has already been parsed.
+    pub fn from_naive(naive: NaiveDateTime) -> Self {
+        Time::Utc(naive)
+    }
+
     pub fn local(instant: Instant) -> Self {
         let local = DateTime::from_local(
             NaiveDateTime::from_timestamp_micros(instant.elapsed().as_micros() as i64).unwrap(),
@@ -40,17 +45,54 @@ impl Time {
         Time::Monotonic(instant)
     }
 
+    /// Returns a new time offset by the given number of seconds, or None if the result
+    /// is out of range.
+    pub fn checked_add_seconds(&self, seconds: i64) -> Option<Self> {
+        // Duration::seconds panics when out of range, milliseconds covers all of i64.
+        let duration = Duration::milliseconds(seconds.checked_mul(1000)?);
+
+        match *self {
+            Time::Utc(utc) => utc.checked_add_signed(duration).map(Time::Utc),
+            Time::Local(local) => local.checked_add_signed(duration).map(Time::Local),
+            Time::Monotonic(instant) => {
+                let offset = StdDuration::from_secs(seconds.unsigned_abs());
+
+                if seconds < 0 {
+                    instant.checked_sub(offset).map(Time::Monotonic)
+                } else {
+                    instant.checked_add(offset).map(Time::Monotonic)
+                }
+            }
+        }
+    }
+
+    /// Returns the number of whole seconds from `earlier` to this time. The result is
+    /// negative if `earlier` is actually later.
+    pub fn seconds_since(&self, earlier: &Time) -> i64 {
+        if let (Time::Monotonic(instant), Time::Monotonic(earlier)) = (self, earlier) {
+            return match instant.checked_duration_since(*earlier) {
+                Some(duration) => duration.as_secs() as i64,
+                None => -(earlier.duration_since(*instant).as_secs() as i64),
+            };
+        }
+
+        (self.as_date_time() - earlier.as_date_time()).num_seconds()
+    }
+
     pub fn as_local(&self) -> String {
-        let date_time = match *self {
+        self.as_date_time().to_string()
+    }
+
+    /// Returns the underlying timestamp with its offset from UTC.
+    pub fn as_date_time(&self) -> DateTime<LocalTime> {
+        match *self {
             Time::Utc(utc) => DateTime::from_utc(utc, FixedOffset::west_opt(0).unwrap()),
             Time::Local(local) => local,
             Time::Monotonic(instant) => DateTime::from_utc(
                 NaiveDateTime::from_timestamp_micros(instant.elapsed().as_micros() as i64).unwrap(),
                 FixedOffset::west_opt(0).unwrap(),
             ),
-        };
-
-        date_time.to_string()
+        }
     }
 }
 
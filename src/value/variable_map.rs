@@ -1,42 +1,90 @@
+use indicatif::ProgressBar;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeMap,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Display, Formatter},
+    time::Duration,
 };
 
-use crate::{value::Value, Error, Result, Table, MACRO_LIST};
+use crate::{value::Value, Error, Function, Macro, Result, Table, MACRO_LIST};
 
 /// A context that stores its mappings in hash maps.
-#[derive(Clone, Debug, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct VariableMap {
     variables: BTreeMap<String, Value>,
+    #[serde(skip)]
+    constants: BTreeSet<String>,
+    #[serde(skip)]
+    show_spinner: bool,
 }
 
+/// Macro groups that may block for a long time and show a spinner while they run. Macros
+/// that hand stdin to a child process are skipped because the child may prompt the user.
+const SPINNER_GROUPS: [&str; 3] = ["command", "network", "package management"];
+
 impl VariableMap {
     /// Creates a new instace.
     pub fn new() -> Self {
         VariableMap {
             variables: BTreeMap::new(),
+            constants: BTreeSet::new(),
+            show_spinner: false,
         }
     }
 
+    /// Show a terminal spinner while slow macros, like shell commands and downloads, run.
+    pub fn set_show_spinner(&mut self, show_spinner: bool) {
+        self.show_spinner = show_spinner;
+    }
+
+    /// Calls a macro or a function stored in this map. Macros may read the variables, but
+    /// macros that change them, like `unset`, return [Error::ContextNotMutable] here; use
+    /// [VariableMap::call_function_mut] for those.
     pub fn call_function(&self, identifier: &str, argument: &Value) -> Result<Value> {
-        for macro_item in MACRO_LIST {
-            if identifier == macro_item.info().identifier {
-                return macro_item.run(argument);
+        match self.resolve_call(identifier, argument)? {
+            (Callee::Macro(macro_item), argument) => {
+                if self.shows_spinner_for(macro_item, &argument) {
+                    with_spinner(macro_item.info().identifier, || {
+                        macro_item.run_with_read_context(&argument, self)
+                    })
+                } else {
+                    macro_item.run_with_read_context(&argument, self)
+                }
             }
+            (Callee::Function(function), argument) => call_stored_function(&function, argument),
         }
+    }
 
-        for (key, value) in &self.variables {
-            if identifier == key {
-                if let Ok(function) = value.as_function() {
-                    let mut context = VariableMap::new();
-
-                    context.set_value("input", argument.clone())?;
-
-                    return function.run_with_context(&mut context);
+    /// Like [VariableMap::call_function], but macros such as `unset` and `load_context`
+    /// may change the variables in this map.
+    pub fn call_function_mut(&mut self, identifier: &str, argument: &Value) -> Result<Value> {
+        match self.resolve_call(identifier, argument)? {
+            (Callee::Macro(macro_item), argument) => {
+                if self.shows_spinner_for(macro_item, &argument) {
+                    with_spinner(macro_item.info().identifier, || {
+                        macro_item.run_with_context(&argument, self)
+                    })
+                } else {
+                    macro_item.run_with_context(&argument, self)
                 }
             }
+            (Callee::Function(function), argument) => call_stored_function(&function, argument),
+        }
+    }
+
+    /// Finds what an identifier refers to and the argument it should be called with. An
+    /// identifier like `variable:function` passes the variable's value as the argument.
+    fn resolve_call(&self, identifier: &str, argument: &Value) -> Result<(Callee, Value)> {
+        for macro_item in MACRO_LIST {
+            if identifier == macro_item.info().identifier {
+                return Ok((Callee::Macro(macro_item), argument.clone()));
+            }
+        }
+
+        if let Some(Value::Function(function)) = self.variables.get(identifier) {
+            return Ok((Callee::Function(function.clone()), argument.clone()));
         }
 
         let mut split = identifier.split(':').rev();
@@ -44,41 +92,43 @@ impl VariableMap {
         if let (Some(function_identifier), Some(variable_identifier)) = (split.next(), split.next())
         {
             if function_identifier.contains(':') {
-                return self.call_function(function_identifier, argument);
+                return self.resolve_call(function_identifier, argument);
             }
 
             if variable_identifier.split_once('.').is_some() {
                 let value = self.get_value(variable_identifier)?.unwrap_or(Value::Empty);
 
-                return self.call_function(function_identifier, &value);
+                return self.resolve_call(function_identifier, &value);
             }
 
             if let Some(value) = self.get_value(variable_identifier)? {
                 if argument.is_empty() {
-                    return self.call_function(function_identifier, &value);
+                    return self.resolve_call(function_identifier, &value);
                 }
 
                 let list = Value::List(vec![value, argument.clone()]);
 
-                return self.call_function(function_identifier, &list);
+                return self.resolve_call(function_identifier, &list);
             }
         }
 
         Err(Error::FunctionIdentifierNotFound(identifier.to_string()))
     }
 
+    fn shows_spinner_for(&self, macro_item: &dyn Macro, argument: &Value) -> bool {
+        should_show_spinner(
+            self.show_spinner,
+            macro_item.info().group,
+            macro_item.inherits_stdin(argument),
+        )
+    }
+
     pub fn get_value(&self, identifier: &str) -> Result<Option<Value>> {
         let split = identifier.split_once('.');
 
         if let Some((identifier, next_identifier)) = split {
             if let Some(value) = self.variables.get(identifier) {
-                if let Value::Map(map) = value {
-                    map.get_value(next_identifier)
-                } else {
-                    Err(Error::ExpectedMap {
-                        actual: value.clone(),
-                    })
-                }
+                get_nested_value(value, next_identifier)
             } else {
                 Ok(None)
             }
@@ -94,19 +144,21 @@ impl VariableMap {
     }
 
     pub fn set_value(&mut self, identifier: &str, value: Value) -> Result<()> {
+        let variable_name = identifier.split('.').next().unwrap_or(identifier);
+
+        if self.constants.contains(variable_name) {
+            return Err(Error::CustomMessage(format!(
+                "Cannot assign to \"{variable_name}\" because it is a constant."
+            )));
+        }
+
         let split = identifier.split_once('.');
 
         if let Some((map_name, next_identifier)) = split {
             let get_value = self.variables.get_mut(map_name);
 
-            if let Some(map_value) = get_value {
-                if let Value::Map(map) = map_value {
-                    map.set_value(next_identifier, value)
-                } else {
-                    Err(Error::ExpectedMap {
-                        actual: map_value.clone(),
-                    })
-                }
+            if let Some(collection) = get_value {
+                set_nested_value(collection, next_identifier, value)
             } else {
                 let mut new_map = VariableMap::new();
 
@@ -124,6 +176,94 @@ impl VariableMap {
         }
     }
 
+    /// Removes a variable or a nested item, returning the removed value if it existed.
+    pub fn remove_value(&mut self, identifier: &str) -> Result<Option<Value>> {
+        let variable_name = identifier.split('.').next().unwrap_or(identifier);
+
+        if self.constants.contains(variable_name) {
+            return Err(Error::CustomMessage(format!(
+                "Cannot remove \"{variable_name}\" because it is a constant."
+            )));
+        }
+
+        if let Some((collection_name, next_identifier)) = identifier.split_once('.') {
+            if let Some(collection) = self.variables.get_mut(collection_name) {
+                remove_nested_value(collection, next_identifier)
+            } else {
+                Ok(None)
+            }
+        } else {
+            Ok(self.variables.remove(identifier))
+        }
+    }
+
+    /// Binds a value that cannot be reassigned for as long as this map exists. Only top-level
+    /// variables can be constant, so dotted paths like `config.port` are rejected.
+    pub fn set_constant(&mut self, identifier: &str, value: Value) -> Result<()> {
+        if identifier.contains('.') {
+            return Err(Error::CustomMessage(format!(
+                "Cannot make \"{identifier}\" a constant. Only top-level variables can be constant."
+            )));
+        }
+
+        self.set_value(identifier, value)?;
+        self.constants.insert(identifier.to_string());
+
+        Ok(())
+    }
+
+    /// Returns the names of the variables bound with [VariableMap::set_constant].
+    pub fn constants(&self) -> impl Iterator<Item = &String> {
+        self.constants.iter()
+    }
+
+    /// Returns the full names of the variables directly under a dotted prefix, e.g.
+    /// `config.port` for the prefix `config`. An empty prefix lists the top-level names.
+    pub fn keys_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        if prefix.is_empty() {
+            return Ok(self.variables.keys().cloned().collect());
+        }
+
+        match self.get_value(prefix)? {
+            Some(Value::Map(map)) => Ok(map
+                .variables
+                .keys()
+                .map(|key| format!("{prefix}.{key}"))
+                .collect()),
+            Some(value) => Err(Error::ExpectedMap { actual: value }),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Copies every variable from `other` into this map, replacing existing values. When
+    /// both maps hold a map under the same name, the two are merged recursively instead.
+    pub fn extend(&mut self, other: &VariableMap) {
+        for (key, value) in &other.variables {
+            match (self.variables.get_mut(key), value) {
+                (Some(Value::Map(existing)), Value::Map(incoming)) => existing.extend(incoming),
+                _ => {
+                    self.variables.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    /// Returns the top-level variable names in sorted order.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.variables.keys()
+    }
+
+    /// Returns the top-level values, sorted by their variable names.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.variables.values()
+    }
+
+    /// Returns true if a value exists at the identifier, which may be a dotted path like
+    /// `config.port`.
+    pub fn contains_key(&self, identifier: &str) -> bool {
+        matches!(self.get_value(identifier), Ok(Some(_)))
+    }
+
     /// Returns a reference to the inner BTreeMap.
     pub fn inner(&self) -> &BTreeMap<String, Value> {
         &self.variables
@@ -140,6 +280,27 @@ impl VariableMap {
     }
 }
 
+/// Maps are compared by their variables only. Constants and display settings are ignored.
+impl PartialEq for VariableMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.variables == other.variables
+    }
+}
+
+impl Eq for VariableMap {}
+
+impl PartialOrd for VariableMap {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for VariableMap {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.variables.cmp(&other.variables)
+    }
+}
+
 impl Default for VariableMap {
     fn default() -> Self {
         Self::new()
@@ -165,6 +326,128 @@ impl From<&Table> for VariableMap {
     }
 }
 
+impl From<BTreeMap<String, Value>> for VariableMap {
+    /// Wraps the map as is. Unlike [VariableMap::set_value], keys are not split on dots.
+    fn from(variables: BTreeMap<String, Value>) -> Self {
+        VariableMap {
+            variables,
+            ..VariableMap::new()
+        }
+    }
+}
+
+/// What an identifier passed to [VariableMap::call_function] refers to.
+enum Callee {
+    Macro(&'static dyn Macro),
+    Function(Function),
+}
+
+/// Runs a function value with the argument bound to `input` in a fresh context.
+fn call_stored_function(function: &Function, argument: Value) -> Result<Value> {
+    let mut context = VariableMap::new();
+
+    context.set_value("input", argument)?;
+    function.run_with_context(&mut context)
+}
+
+/// Decides whether a macro from the given group gets a spinner while it runs.
+fn should_show_spinner(show_spinner: bool, group: &str, inherits_stdin: bool) -> bool {
+    show_spinner && SPINNER_GROUPS.contains(&group) && !inherits_stdin
+}
+
+/// Runs the closure while a spinner with the given message is drawn to stderr.
+fn with_spinner<T>(message: &str, run: impl FnOnce() -> T) -> T {
+    let spinner = ProgressBar::new_spinner();
+
+    spinner.set_message(message.to_string());
+    spinner.enable_steady_tick(Duration::from_millis(100));
+
+    let result = run();
+
+    spinner.finish_and_clear();
+
+    result
+}
+
+/// Reads the value at a dot-separated path inside of a map or list, e.g. `key.0`.
+fn get_nested_value(collection: &Value, path: &str) -> Result<Option<Value>> {
+    match collection {
+        Value::Map(map) => map.get_value(path),
+        Value::List(list) => {
+            let (index, rest) = split_list_index(path)?;
+
+            match (list.get(index), rest) {
+                (Some(item), Some(rest)) => get_nested_value(item, rest),
+                (item, _) => Ok(item.cloned()),
+            }
+        }
+        _ => Err(Error::ExpectedMap {
+            actual: collection.clone(),
+        }),
+    }
+}
+
+/// Overwrites the value at a dot-separated path inside of a map or list, e.g. `key.0`.
+fn set_nested_value(collection: &mut Value, path: &str, value: Value) -> Result<()> {
+    match collection {
+        Value::Map(map) => map.set_value(path, value),
+        Value::List(list) => {
+            let (index, rest) = split_list_index(path)?;
+            let length = list.len();
+            let item = list.get_mut(index).ok_or_else(|| {
+                Error::CustomMessage(format!(
+                    "Index {index} is out of bounds for a list of length {length}."
+                ))
+            })?;
+
+            if let Some(rest) = rest {
+                set_nested_value(item, rest, value)
+            } else {
+                *item = value;
+
+                Ok(())
+            }
+        }
+        _ => Err(Error::ExpectedMap {
+            actual: collection.clone(),
+        }),
+    }
+}
+
+/// Removes the value at a dot-separated path inside of a map or list, e.g. `key.0`.
+fn remove_nested_value(collection: &mut Value, path: &str) -> Result<Option<Value>> {
+    match collection {
+        Value::Map(map) => map.remove_value(path),
+        Value::List(list) => {
+            let (index, rest) = split_list_index(path)?;
+
+            match rest {
+                Some(rest) => match list.get_mut(index) {
+                    Some(item) => remove_nested_value(item, rest),
+                    None => Ok(None),
+                },
+                None if index < list.len() => Ok(Some(list.remove(index))),
+                None => Ok(None),
+            }
+        }
+        _ => Err(Error::ExpectedMap {
+            actual: collection.clone(),
+        }),
+    }
+}
+
+fn split_list_index(path: &str) -> Result<(usize, Option<&str>)> {
+    let (index, rest) = match path.split_once('.') {
+        Some((index, rest)) => (index, Some(rest)),
+        None => (path, None),
+    };
+    let index = index.parse().map_err(|_| Error::ExpectedInt {
+        actual: Value::String(index.to_string()),
+    })?;
+
+    Ok((index, rest))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,4 +478,219 @@ mod tests {
             map.get_value("x.x.x.x").unwrap().unwrap()
         );
     }
+
+    #[test]
+    fn constant_cannot_be_reassigned() {
+        let mut map = VariableMap::new();
+
+        map.set_constant("x", Value::Integer(1)).unwrap();
+
+        assert!(map.set_value("x", Value::Integer(2)).is_err());
+        assert!(map.set_constant("x", Value::Integer(2)).is_err());
+        assert_eq!(Value::Integer(1), map.get_value("x").unwrap().unwrap());
+    }
+
+    #[test]
+    fn dotted_constant_is_rejected() {
+        let mut map = VariableMap::new();
+
+        assert!(map.set_constant("a.b", Value::Integer(1)).is_err());
+        assert_eq!(None, map.get_value("a").unwrap());
+    }
+
+    #[test]
+    fn constants_do_not_affect_equality() {
+        let mut constant = VariableMap::new();
+        let mut variable = VariableMap::new();
+
+        constant.set_constant("x", Value::Integer(1)).unwrap();
+        variable.set_value("x", Value::Integer(1)).unwrap();
+        variable.set_show_spinner(true);
+
+        assert_eq!(constant, variable);
+        assert_eq!(Ordering::Equal, constant.cmp(&variable));
+    }
+
+    #[test]
+    fn get_and_set_list_items() {
+        let mut map = VariableMap::new();
+
+        map.set_value("x", Value::List(vec![Value::Integer(1), Value::Integer(2)]))
+            .unwrap();
+        map.set_value("x.1", Value::Integer(3)).unwrap();
+
+        assert_eq!(Value::Integer(3), map.get_value("x.1").unwrap().unwrap());
+        assert_eq!(None, map.get_value("x.2").unwrap());
+        assert!(map.set_value("x.2", Value::Integer(3)).is_err());
+    }
+
+    #[test]
+    fn remove_simple_and_nested_values() {
+        let mut map = VariableMap::new();
+
+        map.set_value("x", Value::Integer(1)).unwrap();
+        map.set_value("y.z", Value::Integer(2)).unwrap();
+
+        assert_eq!(Some(Value::Integer(1)), map.remove_value("x").unwrap());
+        assert_eq!(Some(Value::Integer(2)), map.remove_value("y.z").unwrap());
+        assert_eq!(None, map.remove_value("x").unwrap());
+        assert_eq!(None, map.get_value("x").unwrap());
+        assert_eq!(None, map.get_value("y.z").unwrap());
+        assert!(map.get_value("y").unwrap().is_some());
+    }
+
+    #[test]
+    fn keys_with_prefix() {
+        let mut map = VariableMap::new();
+
+        map.set_value("config.host", Value::String("localhost".to_string()))
+            .unwrap();
+        map.set_value("config.port", Value::Integer(8080)).unwrap();
+        map.set_value("other", Value::Empty).unwrap();
+
+        assert_eq!(
+            vec!["config.host".to_string(), "config.port".to_string()],
+            map.keys_with_prefix("config").unwrap()
+        );
+        assert_eq!(
+            vec!["config".to_string(), "other".to_string()],
+            map.keys_with_prefix("").unwrap()
+        );
+    }
+
+    #[test]
+    fn with_spinner_returns_result() {
+        let result = with_spinner("test", || Value::Integer(1));
+
+        assert_eq!(Value::Integer(1), result);
+    }
+
+    #[test]
+    fn spinner_decision() {
+        assert!(should_show_spinner(true, "network", false));
+        assert!(should_show_spinner(true, "command", false));
+        assert!(should_show_spinner(true, "package management", false));
+        assert!(!should_show_spinner(true, "command", true));
+        assert!(!should_show_spinner(true, "package management", true));
+        assert!(!should_show_spinner(true, "math", false));
+        assert!(!should_show_spinner(false, "network", false));
+    }
+
+    #[test]
+    fn spinner_is_shown_for_slow_macro_groups() {
+        let mut map = VariableMap::new();
+
+        map.set_show_spinner(true);
+
+        for macro_item in MACRO_LIST {
+            let info = macro_item.info();
+            let is_slow = ["command", "network", "package management"].contains(&info.group);
+            let expected = is_slow && !macro_item.inherits_stdin(&Value::Empty);
+
+            assert_eq!(expected, map.shows_spinner_for(macro_item, &Value::Empty));
+        }
+    }
+
+    #[test]
+    fn spinner_is_not_shown_over_commands_that_inherit_stdin() {
+        let mut map = VariableMap::new();
+        let find_macro = |identifier| {
+            *MACRO_LIST
+                .iter()
+                .find(|macro_item| macro_item.info().identifier == identifier)
+                .unwrap()
+        };
+        let command = Value::String("true".to_string());
+        let captured = Value::List(vec![command.clone(), Value::Boolean(false)]);
+
+        map.set_show_spinner(true);
+
+        assert!(!map.shows_spinner_for(find_macro("bash"), &command));
+        assert!(map.shows_spinner_for(find_macro("bash"), &captured));
+        assert!(map.shows_spinner_for(find_macro("exec_pipeline"), &Value::Empty));
+        assert!(!map.shows_spinner_for(find_macro("install_package"), &Value::Empty));
+    }
+
+    #[test]
+    fn context_macros_need_a_mutable_map() {
+        let mut map = VariableMap::new();
+
+        map.set_value("x", Value::Integer(1)).unwrap();
+
+        assert_eq!(
+            Err(Error::ContextNotMutable),
+            map.call_function("unset", &Value::String("x".to_string()))
+        );
+        assert_eq!(Some(Value::Integer(1)), map.get_value("x").unwrap());
+
+        map.call_function_mut("unset", &Value::String("x".to_string()))
+            .unwrap();
+
+        assert_eq!(None, map.get_value("x").unwrap());
+    }
+
+    #[test]
+    fn read_only_context_macros_work_without_a_mutable_map() {
+        let mut map = VariableMap::new();
+
+        map.set_value("x.y", Value::Integer(1)).unwrap();
+
+        assert_eq!(
+            Ok(Value::Map(map.clone())),
+            map.call_function("context", &Value::Empty)
+        );
+        assert_eq!(
+            Ok(Value::List(vec![Value::String("x.y".to_string())])),
+            map.call_function("vars", &Value::String("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn keys_values_and_contains_key() {
+        let mut map = VariableMap::new();
+
+        map.set_value("x.y", Value::Integer(1)).unwrap();
+        map.set_value("z", Value::Integer(2)).unwrap();
+
+        assert_eq!(vec!["x", "z"], map.keys().collect::<Vec<_>>());
+        assert_eq!(Some(&Value::Integer(2)), map.values().last());
+        assert!(map.contains_key("x"));
+        assert!(map.contains_key("x.y"));
+        assert!(!map.contains_key("x.w"));
+        assert!(!map.contains_key("z.y"));
+        assert!(!map.contains_key("w"));
+    }
+
+    #[test]
+    fn extend_merges_nested_maps() {
+        let mut left = VariableMap::new();
+        let mut right = VariableMap::new();
+
+        left.set_value("name", Value::String("left".to_string()))
+            .unwrap();
+        left.set_value("config.port", Value::Integer(80)).unwrap();
+        left.set_value("config.host", Value::String("localhost".to_string()))
+            .unwrap();
+        right
+            .set_value("name", Value::String("right".to_string()))
+            .unwrap();
+        right
+            .set_value("config.port", Value::Integer(8080))
+            .unwrap();
+
+        left.extend(&right);
+
+        assert_eq!(
+            Some(Value::String("right".to_string())),
+            left.get_value("name").unwrap()
+        );
+        assert_eq!(
+            Some(Value::Integer(8080)),
+            left.get_value("config.port").unwrap()
+        );
+        assert_eq!(
+            Some(Value::String("localhost".to_string())),
+            left.get_value("config.host").unwrap()
+        );
+    }
 }